@@ -0,0 +1,73 @@
+//! Writes a pack of generated puzzles to stdout, ready to be played with `--puzzles`.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rust_minesweeper::game::generator::{generate, Technique};
+use rust_minesweeper::game::geometry::Geometry;
+use rust_minesweeper::game::puzzle::Puzzle;
+use rust_minesweeper::game::settings::Settings;
+
+const USAGE: &str =
+    "Usage: rust-minesweeper-puzzles [--count <number>] [--difficulty easy|medium|hard]
+    [--size ...] [--grid ...] [--kernel ...] [--seed <number>]";
+const DEFAULT_COUNT: u32 = 10;
+const AUTHOR: &str = "rust-minesweeper-puzzles";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut count = DEFAULT_COUNT;
+    let mut allowed = Technique::Subset;
+    // the board options are passed on to the parser of the game
+    let mut board_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--count" => match args.next().map(|value| value.parse()) {
+                Some(Ok(value)) => count = value,
+                _ => exit_with(USAGE),
+            },
+            "--difficulty" => match args.next().as_deref().and_then(Technique::parse) {
+                Some(technique) => allowed = technique,
+                None => exit_with(USAGE),
+            },
+            _ => board_args.push(arg),
+        }
+    }
+    let settings = Settings::from_args(board_args.into_iter()).unwrap_or_else(|e| {
+        exit_with(e.lines().next().unwrap_or_default());
+    });
+    // packs have no place for more mines in a cell
+    if settings.mines_per_cell != 1 {
+        exit_with("Puzzles have one mine per cell");
+    }
+    let geometry = Geometry::new(settings.grid, settings.kernel.clone());
+    let mut rng = match settings.seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    };
+    // without a name the pack is named after its file, so solved puzzles of different
+    // packs are told apart
+    println!("# Generated with {}", AUTHOR);
+    println!("author = {}", AUTHOR);
+    for number in 1..=count {
+        let Some((layout, technique)) = generate(settings.field_size, &geometry, allowed, &mut rng)
+        else {
+            exit_with("The board is too small for puzzles");
+        };
+        let puzzle = Puzzle::new(
+            format!("Generated {}", number),
+            String::from(technique.difficulty()),
+            String::from(AUTHOR),
+            settings.grid,
+            settings.kernel.clone(),
+            settings.field_size,
+            layout,
+        );
+        println!();
+        print!("{}", puzzle.write());
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(2);
+}
@@ -0,0 +1,321 @@
+//! Hosts games over a JSON HTTP API. The mines never leave the server, clients only get
+//! what a player could see.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use piston::UpdateArgs;
+use rust_minesweeper::game::geometry::GridKind;
+use rust_minesweeper::game::settings::Settings;
+use rust_minesweeper::game::{CellCommand, Game, GameElement, GameState, Point};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const USAGE: &str = "Usage: rust-minesweeper-server [--port <port>]";
+const DEFAULT_PORT: u16 = 8080;
+const MAX_BODY: u64 = 64 * 1024;
+
+/// Options of a new game, the same as on the command line of the game.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct NewGame {
+    size: Option<String>,
+    grid: Option<String>,
+    kernel: Option<String>,
+    mines_per_cell: Option<u8>,
+    seed: Option<u64>,
+}
+
+impl NewGame {
+    // goes through the command line parser, so the options are checked the same way
+    fn settings(&self) -> Result<Settings, String> {
+        let mut args = Vec::new();
+        let mut option = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(name.to_string());
+                args.push(value);
+            }
+        };
+        option("--size", self.size.clone());
+        option("--grid", self.grid.clone());
+        option("--kernel", self.kernel.clone());
+        option(
+            "--mines-per-cell",
+            self.mines_per_cell.map(|m| m.to_string()),
+        );
+        option("--seed", self.seed.map(|seed| seed.to_string()));
+        let mut settings = Settings::from_args(args.into_iter()).map_err(|e| {
+            // the usage of the game's command line means nothing to API clients
+            e.lines().next().unwrap_or_default().to_string()
+        })?;
+        settings.animations = false;
+        Ok(settings)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MoveRequest {
+    command: CellCommand,
+    x: u32,
+    y: u32,
+}
+
+/// What a player can see of a game.
+#[derive(Serialize)]
+struct GameView<'a> {
+    id: u64,
+    state: GameState,
+    time: f64,
+    grid: GridKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kernel: Option<&'a [(i64, i64)]>,
+    width: u32,
+    height: u32,
+    mines: u32,
+    flags: u32,
+    cells: Vec<Vec<String>>,
+}
+
+struct HostedGame {
+    game: Game,
+    // the game timer runs only when the game is updated
+    updated: Instant,
+}
+
+impl HostedGame {
+    fn new(settings: Settings) -> HostedGame {
+        HostedGame {
+            game: Game::new(settings),
+            updated: Instant::now(),
+        }
+    }
+
+    fn update(&mut self) {
+        let now = Instant::now();
+        let dt = now.duration_since(self.updated).as_secs_f64();
+        self.game.update(&UpdateArgs { dt });
+        self.updated = now;
+    }
+
+    fn view(&self, id: u64) -> GameView<'_> {
+        let field = self.game.field();
+        let size = field.size();
+        GameView {
+            id,
+            state: self.game.state(),
+            time: self.game.time(),
+            grid: field.geometry().kind(),
+            kernel: field.geometry().kernel_offsets(),
+            width: size.width,
+            height: size.height,
+            mines: size.mines,
+            flags: field.flags(),
+            cells: field
+                .view()
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.token()).collect())
+                .collect(),
+        }
+    }
+}
+
+type Reply = Result<(u16, Value), (u16, String)>;
+
+#[derive(Default)]
+struct Games {
+    games: HashMap<u64, HostedGame>,
+    next_id: u64,
+}
+
+impl Games {
+    fn handle(&mut self, method: &Method, path: &str, body: &str) -> Reply {
+        let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, parts.as_slice()) {
+            (Method::Get, ["games"]) => Ok((200, self.list())),
+            (Method::Post, ["games"]) => self.create(body),
+            (Method::Get, ["games", id]) => {
+                let (id, hosted) = self.game(id)?;
+                Ok((200, json!(hosted.view(id))))
+            }
+            (Method::Delete, ["games", id]) => {
+                let (id, _) = self.game(id)?;
+                self.games.remove(&id);
+                Ok((200, json!({ "id": id })))
+            }
+            (Method::Post, ["games", id, "moves"]) => {
+                let request: MoveRequest = parse_body(body)?;
+                let (id, hosted) = self.game(id)?;
+                let cell = Point {
+                    x: request.x,
+                    y: request.y,
+                };
+                if !hosted.game.field().contains(cell) {
+                    return Err((
+                        400,
+                        format!("Cell {},{} is outside of the board", cell.x, cell.y),
+                    ));
+                }
+                let result = hosted.game.command(request.command, cell);
+                Ok((
+                    200,
+                    json!({ "result": result.name(), "game": hosted.view(id) }),
+                ))
+            }
+            (Method::Get, ["games", id, "result"]) => {
+                let (id, hosted) = self.game(id)?;
+                let state = hosted.game.state();
+                Ok((
+                    200,
+                    json!({
+                        "id": id,
+                        "finished": state == GameState::Win || state == GameState::Loose,
+                        "won": state == GameState::Win,
+                        "time": hosted.game.time(),
+                        "cleared": hosted.game.field().cleared(),
+                    }),
+                ))
+            }
+            (_, ["games"]) | (_, ["games", _]) | (_, ["games", _, "moves" | "result"]) => {
+                Err((405, format!("{} is not allowed on {}", method, path)))
+            }
+            _ => Err((404, format!("Nothing at {}", path))),
+        }
+    }
+
+    fn list(&self) -> Value {
+        let mut ids: Vec<&u64> = self.games.keys().collect();
+        ids.sort();
+        let games: Vec<Value> = ids
+            .into_iter()
+            .map(|id| json!({ "id": id, "state": self.games[id].game.state() }))
+            .collect();
+        json!({ "games": games })
+    }
+
+    fn create(&mut self, body: &str) -> Reply {
+        let new_game: NewGame = if body.trim().is_empty() {
+            NewGame::default()
+        } else {
+            parse_body(body)?
+        };
+        let settings = new_game.settings().map_err(|e| (400, e))?;
+        self.next_id += 1;
+        let id = self.next_id;
+        let hosted = HostedGame::new(settings);
+        let view = json!(hosted.view(id));
+        self.games.insert(id, hosted);
+        Ok((201, view))
+    }
+
+    // a bug in one game shouldn't end the others
+    fn handle_caught(
+        &mut self,
+        method: &Method,
+        path: &str,
+        body: &str,
+        handle: fn(&mut Games, &Method, &str, &str) -> Reply,
+    ) -> Reply {
+        panic::catch_unwind(AssertUnwindSafe(|| handle(self, method, path, body))).unwrap_or_else(
+            |_| {
+                self.remove_failed(path);
+                Err((500, format!("{} {} has failed", method, path)))
+            },
+        )
+    }
+
+    // the game of a request which panicked may be left broken
+    fn remove_failed(&mut self, path: &str) {
+        let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+        if let ["games", id, ..] = parts.as_slice() {
+            if let Ok(id) = id.parse() {
+                self.games.remove(&id);
+            }
+        }
+    }
+
+    fn game(&mut self, id: &str) -> Result<(u64, &mut HostedGame), (u16, String)> {
+        let not_found = || (404, format!("No game {}", id));
+        let id: u64 = id.parse().map_err(|_| not_found())?;
+        let hosted = self.games.get_mut(&id).ok_or_else(not_found)?;
+        hosted.update();
+        Ok((id, hosted))
+    }
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, (u16, String)> {
+    serde_json::from_str(body).map_err(|e| (400, format!("Can't parse request: {}", e)))
+}
+
+fn respond(mut request: Request, games: &mut Games) {
+    let mut body = String::new();
+    let reply = match request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+        Ok(_) => {
+            let path = request
+                .url()
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let method = request.method().clone();
+            games.handle_caught(&method, &path, &body, Games::handle)
+        }
+        Err(e) => Err((400, format!("Can't read request: {}", e))),
+    };
+    let (status, value) =
+        reply.unwrap_or_else(|(status, message)| (status, json!({ "error": message })));
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("Header should be valid");
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Can't send response: {}", e);
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut port = DEFAULT_PORT;
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next().map(|value| value.parse())) {
+            ("--port", Some(Ok(value))) => port = value,
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+    let server = Server::http(("0.0.0.0", port)).unwrap_or_else(|e| {
+        eprintln!("Can't listen on port {}: {}", port, e);
+        std::process::exit(2);
+    });
+    eprintln!("Serving games on port {}", port);
+    let mut games = Games::default();
+    for request in server.incoming_requests() {
+        respond(request, &mut games);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_removes_only_its_game() {
+        let mut games = Games::default();
+        games.create("").unwrap();
+        games.create("").unwrap();
+        let reply = games.handle_caught(&Method::Get, "/games/1", "", |_, _, _, _| {
+            panic!("broken game")
+        });
+        assert_eq!(reply.err().map(|(status, _)| status), Some(500));
+        assert!(!games.games.contains_key(&1));
+        assert!(games.games.contains_key(&2));
+        let reply = games.handle_caught(&Method::Get, "/games/2", "", Games::handle);
+        assert_eq!(reply.ok().map(|(status, _)| status), Some(200));
+    }
+}
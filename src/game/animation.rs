@@ -0,0 +1,54 @@
+use crate::game::Point;
+
+// all times are in seconds
+const CASCADE_STEP: f64 = 0.025;
+const MAX_CASCADE: f64 = 1.0;
+const CHAIN_STEP: f64 = 0.08;
+const MAX_CHAIN: f64 = 2.0;
+const EXPLOSION_TIME: f64 = 0.6;
+const CELEBRATION_TIME: f64 = 1.5;
+
+/// Delay before a cell opened by a cascade is shown, every wave of
+/// neighbours appears a bit later than the previous one.
+pub fn cascade_delay(wave: u32) -> f64 {
+    (wave as f64 * CASCADE_STEP).min(MAX_CASCADE)
+}
+
+/// Delay before a mine is revealed after the explosion, `distance` is in cells
+/// from the mine which was hit.
+pub fn chain_delay(distance: f64) -> f64 {
+    EXPLOSION_TIME / 2.0 + (distance * CHAIN_STEP).min(MAX_CHAIN)
+}
+
+/// Animation drawn over the whole field.
+pub enum Effect {
+    Explosion { center: Point<u32>, time: f64 },
+    Celebration { time: f64 },
+}
+
+impl Effect {
+    pub fn explosion(center: Point<u32>) -> Effect {
+        Effect::Explosion { center, time: 0.0 }
+    }
+
+    pub fn celebration() -> Effect {
+        Effect::Celebration { time: 0.0 }
+    }
+
+    /// Advances the effect, returns false once it is over.
+    pub fn update(&mut self, dt: f64) -> bool {
+        match self {
+            Effect::Explosion { time, .. } | Effect::Celebration { time } => *time += dt,
+        }
+        self.progress() < 1.0
+    }
+
+    /// Goes from 0.0 at the start to 1.0 at the end.
+    pub fn progress(&self) -> f64 {
+        let (time, duration) = match self {
+            Effect::Explosion { time, .. } => (*time, EXPLOSION_TIME),
+            Effect::Celebration { time } => (*time, CELEBRATION_TIME),
+        };
+        (time / duration).min(1.0)
+    }
+}
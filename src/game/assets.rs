@@ -0,0 +1,85 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use opengl_graphics::{GlyphCache, TextureSettings};
+
+pub const ASSETS_ENV: &str = "MINESWEEPER_ASSETS";
+const ASSETS_DIR: &str = "assets";
+const DATA_DIR: &str = "rust-minesweeper";
+const FONT: &str = "Roboto-Regular.ttf";
+
+// used when no font is found on the search path, so the binary runs from anywhere
+static EMBEDDED_FONT: &[u8] = include_bytes!("../../assets/Roboto-Regular.ttf");
+
+/// Directory of the game in the XDG data directory, also where records are kept.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    data_home.map(|data_home| data_home.join(DATA_DIR))
+}
+
+/// Directories searched for asset files, in order of priority: the `--assets` flag,
+/// the `MINESWEEPER_ASSETS` variable, `assets` next to the executable, the XDG data
+/// directory and `assets` in the working directory.
+pub struct Assets {
+    search_path: Vec<PathBuf>,
+}
+
+impl Assets {
+    pub fn new(assets_dir: Option<&str>) -> Assets {
+        let mut search_path = Vec::new();
+        search_path.extend(assets_dir.map(PathBuf::from));
+        search_path.extend(env::var_os(ASSETS_ENV).map(PathBuf::from));
+        if let Some(exe_dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            search_path.push(exe_dir.join(ASSETS_DIR));
+        }
+        search_path.extend(data_dir());
+        search_path.push(PathBuf::from(ASSETS_DIR));
+        Assets { search_path }
+    }
+
+    /// Paths which exist as given are used directly, relative ones are also looked up
+    /// in the search path.
+    pub fn find(&self, path: &str) -> Option<PathBuf> {
+        let given = PathBuf::from(path);
+        if given.exists() {
+            return Some(given);
+        }
+        if given.is_absolute() {
+            return None;
+        }
+        self.search_path
+            .iter()
+            .map(|dir| dir.join(&given))
+            .find(|candidate| candidate.exists())
+    }
+
+    /// Like `find`, but the error lists every place which was searched.
+    pub fn require(&self, path: &str) -> Result<PathBuf, String> {
+        self.find(path).ok_or_else(|| {
+            let searched: Vec<String> = self
+                .search_path
+                .iter()
+                .map(|dir| format!("    {}", dir.display()))
+                .collect();
+            format!(
+                "Can't find {}, searched in the working directory and\n{}",
+                path,
+                searched.join("\n")
+            )
+        })
+    }
+
+    pub fn font(&self) -> Result<GlyphCache<'static>, String> {
+        match self.find(FONT) {
+            Some(path) => GlyphCache::new(&path, (), TextureSettings::new())
+                .map_err(|e| format!("Can't load font {}: {}", path.display(), e)),
+            None => GlyphCache::from_bytes(EMBEDDED_FONT, (), TextureSettings::new())
+                .map_err(|_| String::from("Can't load the embedded font")),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::geometry::GridKind;
+use crate::game::net::tokens;
+use crate::game::{CellCommand, Game, GameState, Point};
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum BotCommand {
+    Open { x: u32, y: u32 },
+    Flag { x: u32, y: u32 },
+    Chord { x: u32, y: u32 },
+    NewGame,
+    Board,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BotMessage<'a> {
+    Board {
+        // outcome of the command this board answers, missing for the first board
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<&'static str>,
+        state: GameState,
+        grid: GridKind,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        kernel: Option<&'a [(i64, i64)]>,
+        width: u32,
+        height: u32,
+        mines: u32,
+        flags: u32,
+        cells: Vec<Vec<String>>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// External process playing the game. The bot gets a JSON board on its stdin after
+/// every command it writes as a JSON line to its stdout.
+pub struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    // seconds between commands, so the game can be watched
+    delay: f64,
+    wait: f64,
+    finished: bool,
+}
+
+impl Bot {
+    pub fn spawn(command_line: &str, delay: f64) -> Result<Bot, String> {
+        let mut words = command_line.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| String::from("Bot command is empty"))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Can't start bot {}: {}", command_line, e))?;
+        let stdin = child.stdin.take().expect("Bot stdin should be piped");
+        let stdout = child.stdout.take().expect("Bot stdout should be piped");
+        // reading blocks, so lines are passed to the game loop through a channel
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Bot {
+            child,
+            stdin,
+            lines,
+            delay,
+            wait: 0.0,
+            finished: false,
+        })
+    }
+
+    pub fn start(&mut self, game: &Game) {
+        self.send(&board(game, None));
+    }
+
+    /// Handles at most one command per delay.
+    pub fn update(&mut self, game: &mut Game, dt: f64) {
+        self.wait -= dt;
+        if self.finished || self.wait > 0.0 {
+            return;
+        }
+        let line = match self.lines.try_recv() {
+            Ok(line) => line,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                eprintln!("Bot has closed its output");
+                self.finished = true;
+                return;
+            }
+        };
+        self.wait = self.delay;
+        match serde_json::from_str::<BotCommand>(&line) {
+            Ok(command) => {
+                let result = execute(game, command);
+                self.send(&board(game, Some(result)));
+            }
+            Err(e) => self.send(&BotMessage::Error {
+                message: format!("Can't parse command {}: {}", line, e),
+            }),
+        }
+    }
+
+    fn send(&mut self, message: &BotMessage) {
+        let line = serde_json::to_string(message).expect("Bot message should be serialized");
+        if writeln!(self.stdin, "{}", line).is_err() && !self.finished {
+            eprintln!("Bot has closed its input");
+            self.finished = true;
+        }
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn execute(game: &mut Game, command: BotCommand) -> &'static str {
+    let (command, x, y) = match command {
+        BotCommand::Open { x, y } => (CellCommand::Open, x, y),
+        BotCommand::Flag { x, y } => (CellCommand::Flag, x, y),
+        BotCommand::Chord { x, y } => (CellCommand::Chord, x, y),
+        BotCommand::NewGame => {
+            game.new_game();
+            return "new_game";
+        }
+        BotCommand::Board => return "board",
+    };
+    game.command(command, Point { x, y }).name()
+}
+
+fn board<'a>(game: &'a Game, result: Option<&'static str>) -> BotMessage<'a> {
+    let field = game.field();
+    let size = field.size();
+    // cells are described like in the other protocols, only as the player sees them
+    BotMessage::Board {
+        result,
+        state: game.state(),
+        grid: field.geometry().kind(),
+        kernel: field.geometry().kernel_offsets(),
+        width: size.width,
+        height: size.height,
+        mines: size.mines,
+        flags: field.flags(),
+        cells: tokens(&field.view()),
+    }
+}
@@ -0,0 +1,135 @@
+use crate::game::draw::Rect;
+use crate::game::Point;
+
+const MAX_ZOOM: f64 = 8.0;
+const ZOOM_STEP: f64 = 1.2;
+
+/// Zoom and pan of the field inside the part of the window below the menu.
+/// Zoom is relative to the cell size which fits the whole field into the view.
+pub struct Camera {
+    view: Rect,
+    fit_cell_size: f64,
+    // field size in cells of size 1.0, so it can be scaled with any cell size
+    field_units: [f64; 2],
+    zoom: f64,
+    origin: Point<f64>,
+}
+
+impl Camera {
+    pub fn new(view: Rect, fit_cell_size: f64, field_units: [f64; 2]) -> Camera {
+        let mut camera = Camera {
+            view,
+            fit_cell_size,
+            field_units,
+            zoom: 1.0,
+            origin: Point {
+                x: view.x,
+                y: view.y,
+            },
+        };
+        camera.clamp();
+        camera
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.fit_cell_size * self.zoom
+    }
+
+    /// Top left corner of the field in window coordinates.
+    pub fn origin(&self) -> Point<f64> {
+        self.origin
+    }
+
+    pub fn view(&self) -> Rect {
+        self.view
+    }
+
+    /// Part of the field which is visible in the view, in field coordinates.
+    pub fn visible_area(&self) -> Rect {
+        Rect::new(
+            self.view.x - self.origin.x,
+            self.view.y - self.origin.y,
+            self.view.width,
+            self.view.height,
+        )
+    }
+
+    pub fn shows_whole_field(&self) -> bool {
+        let [width, height] = self.field_size();
+        width <= self.view.width + f64::EPSILON && height <= self.view.height + f64::EPSILON
+    }
+
+    pub fn field_size(&self) -> [f64; 2] {
+        [
+            self.field_units[0] * self.cell_size(),
+            self.field_units[1] * self.cell_size(),
+        ]
+    }
+
+    /// Keeps the zoom and the field point in the middle of the view.
+    pub fn set_view(&mut self, view: Rect, fit_cell_size: f64) {
+        let center = self.to_field_units(Point {
+            x: self.view.x + self.view.width / 2.0,
+            y: self.view.y + self.view.height / 2.0,
+        });
+        self.view = view;
+        self.fit_cell_size = fit_cell_size;
+        self.origin = Point {
+            x: view.x + view.width / 2.0 - center.x * self.cell_size(),
+            y: view.y + view.height / 2.0 - center.y * self.cell_size(),
+        };
+        self.clamp();
+    }
+
+    pub fn zoom_in(&mut self, anchor: Point<f64>) {
+        self.zoom_at(self.zoom * ZOOM_STEP, anchor);
+    }
+
+    pub fn zoom_out(&mut self, anchor: Point<f64>) {
+        self.zoom_at(self.zoom / ZOOM_STEP, anchor);
+    }
+
+    pub fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+        self.clamp();
+    }
+
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.origin.x += dx;
+        self.origin.y += dy;
+        self.clamp();
+    }
+
+    // the field point under the anchor stays in place
+    fn zoom_at(&mut self, zoom: f64, anchor: Point<f64>) {
+        let anchor_units = self.to_field_units(anchor);
+        self.zoom = zoom.clamp(1.0, MAX_ZOOM);
+        self.origin = Point {
+            x: anchor.x - anchor_units.x * self.cell_size(),
+            y: anchor.y - anchor_units.y * self.cell_size(),
+        };
+        self.clamp();
+    }
+
+    fn to_field_units(&self, point: Point<f64>) -> Point<f64> {
+        Point {
+            x: (point.x - self.origin.x) / self.cell_size(),
+            y: (point.y - self.origin.y) / self.cell_size(),
+        }
+    }
+
+    // a field smaller than the view is centered, a larger one can't leave gaps at the edges
+    fn clamp(&mut self) {
+        let [width, height] = self.field_size();
+        self.origin.x = clamp_axis(self.origin.x, self.view.x, self.view.width, width);
+        self.origin.y = clamp_axis(self.origin.y, self.view.y, self.view.height, height);
+    }
+}
+
+fn clamp_axis(origin: f64, view_start: f64, view_length: f64, field_length: f64) -> f64 {
+    if field_length <= view_length {
+        view_start + (view_length - field_length) / 2.0
+    } else {
+        origin.clamp(view_start + view_length - field_length, view_start)
+    }
+}
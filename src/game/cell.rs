@@ -3,28 +3,73 @@ use opengl_graphics::GlGraphics;
 use piston::RenderArgs;
 
 use crate::game::draw::DrawData;
-use crate::game::{GameElement, Point};
-use crate::UpdateArgs;
+use crate::game::geometry::Geometry;
+use crate::game::Point;
+use piston::UpdateArgs;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum CellState {
     Closed,
     Opened,
     Pressed,
-    Flagged,
+    Flagged(u8),
+    // mine or wrong flag shown after the game is lost
+    Revealed,
+    // more flags than mines, which can be on a cell with mines too
+    WrongFlag,
 }
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum CellContent {
     Empty,
-    Mine,
+    Mine(u8),
     Number(u8),
 }
 
+/// What a player can see of a cell, closed cells never tell whether they hide a mine.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CellView {
+    Closed,
+    Flagged(u8),
+    Opened(u8),
+    Exploded(u8),
+    Mine(u8),
+    WrongFlag,
+}
+
+impl CellView {
+    /// Short text form: `#` closed, `F<flags>`, the number of an opened cell,
+    /// `X<mines>` a mine which was hit, `M<mines>` a revealed mine, `W` a wrong flag.
+    pub fn token(self) -> String {
+        match self {
+            CellView::Closed => String::from("#"),
+            CellView::Flagged(flags) => format!("F{}", flags),
+            CellView::Opened(number) => number.to_string(),
+            CellView::Exploded(mines) => format!("X{}", mines),
+            CellView::Mine(mines) => format!("M{}", mines),
+            CellView::WrongFlag => String::from("W"),
+        }
+    }
+
+    pub fn parse(token: &str) -> Option<CellView> {
+        let count = || token.get(1..)?.parse().ok();
+        match token.chars().next()? {
+            '#' if token.len() == 1 => Some(CellView::Closed),
+            'W' if token.len() == 1 => Some(CellView::WrongFlag),
+            'F' => Some(CellView::Flagged(count()?)),
+            'X' => Some(CellView::Exploded(count()?)),
+            'M' => Some(CellView::Mine(count()?)),
+            _ => Some(CellView::Opened(token.parse().ok()?)),
+        }
+    }
+}
+
 pub struct Cell {
     state: CellState,
     content: CellContent,
     position: Point<u32>,
+    // seconds left until an opened or revealed cell is shown, used by animations
+    reveal_delay: f64,
 }
 
 //reads
@@ -42,7 +87,21 @@ impl Cell {
     }
 
     pub fn is_mine(&self) -> bool {
-        self.content == CellContent::Mine
+        matches!(self.content, CellContent::Mine(_))
+    }
+
+    pub fn mines(&self) -> u8 {
+        match self.content {
+            CellContent::Mine(mines) => mines,
+            _ => 0,
+        }
+    }
+
+    pub fn flags(&self) -> u8 {
+        match self.state {
+            CellState::Flagged(flags) => flags,
+            _ => 0,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -52,6 +111,22 @@ impl Cell {
     pub fn content(&self) -> CellContent {
         self.content
     }
+
+    pub fn is_revealing(&self) -> bool {
+        self.reveal_delay > 0.0
+    }
+
+    pub fn view(&self) -> CellView {
+        match (self.state, self.content) {
+            (CellState::Closed | CellState::Pressed, _) => CellView::Closed,
+            (CellState::Flagged(flags), _) => CellView::Flagged(flags),
+            (CellState::Opened, CellContent::Mine(mines)) => CellView::Exploded(mines),
+            (CellState::Opened, CellContent::Number(number)) => CellView::Opened(number),
+            (CellState::Opened, CellContent::Empty) => CellView::Opened(0),
+            (CellState::Revealed, CellContent::Mine(mines)) => CellView::Mine(mines),
+            (CellState::Revealed | CellState::WrongFlag, _) => CellView::WrongFlag,
+        }
+    }
 }
 
 impl Cell {
@@ -60,11 +135,35 @@ impl Cell {
             state: CellState::Closed,
             content: CellContent::Empty,
             position: point,
+            reveal_delay: 0.0,
         }
     }
 
-    pub fn set_mine(&mut self) {
-        self.content = CellContent::Mine;
+    /// Cell of a board known only from what its players see.
+    pub fn from_view(point: Point<u32>, view: CellView) -> Cell {
+        let mut cell = Cell::new(point);
+        match view {
+            CellView::Closed => {}
+            CellView::Flagged(flags) => cell.set_flags(flags),
+            CellView::Opened(number) => {
+                cell.set_number(number);
+                cell.open();
+            }
+            CellView::Exploded(mines) => {
+                cell.content = CellContent::Mine(mines);
+                cell.open();
+            }
+            CellView::Mine(mines) => {
+                cell.content = CellContent::Mine(mines);
+                cell.reveal_after(0.0);
+            }
+            CellView::WrongFlag => cell.reveal_wrong_flag_after(0.0),
+        }
+        cell
+    }
+
+    pub fn add_mine(&mut self) {
+        self.content = CellContent::Mine(self.mines() + 1);
     }
 
     pub fn set_number(&mut self, number: u8) {
@@ -79,8 +178,32 @@ impl Cell {
         self.state = CellState::Opened;
     }
 
+    /// Opens the cell, it still looks closed for the delay.
+    pub fn open_after(&mut self, delay: f64) {
+        self.open();
+        self.reveal_delay = delay;
+    }
+
+    pub fn reveal_after(&mut self, delay: f64) {
+        self.state = CellState::Revealed;
+        self.reveal_delay = delay;
+    }
+
+    pub fn reveal_wrong_flag_after(&mut self, delay: f64) {
+        self.state = CellState::WrongFlag;
+        self.reveal_delay = delay;
+    }
+
+    pub fn skip_reveal(&mut self) {
+        self.reveal_delay = 0.0;
+    }
+
     pub fn flag(&mut self) {
-        self.state = CellState::Flagged;
+        self.state = CellState::Flagged(self.flags() + 1);
+    }
+
+    pub fn set_flags(&mut self, flags: u8) {
+        self.state = CellState::Flagged(flags);
     }
 
     pub fn unflag(&mut self) {
@@ -90,24 +213,49 @@ impl Cell {
     pub fn press(&mut self) {
         self.state = CellState::Pressed;
     }
+
+    pub fn unpress(&mut self) {
+        if self.state == CellState::Pressed {
+            self.state = CellState::Closed;
+        }
+    }
 }
 
-impl GameElement for Cell {
-    fn render(&self, _: &RenderArgs, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
-        let glyph_cache = &mut dd.glyph_cache;
+// the cell shape depends on the field geometry, so it is rendered by the field
+impl Cell {
+    pub fn render(
+        &self,
+        geometry: &Geometry,
+        _: &RenderArgs,
+        c: Context,
+        gl: &mut GlGraphics,
+        dd: &mut DrawData,
+    ) {
+        if self.is_revealing() {
+            super::draw::draw_closed_cell(geometry, self.position, &c, gl, dd);
+            return;
+        }
         match self.state {
-            CellState::Opened => super::draw::draw_opened_cell(
-                self.position.to_f64(),
-                &self.content,
-                &c,
-                gl,
-                glyph_cache,
-            ),
-            CellState::Pressed => super::draw::draw_pressed_cell(self.position.to_f64(), &c, gl),
-            CellState::Flagged => super::draw::draw_flagged_cell(self.position.to_f64(), &c, gl),
-            _ => super::draw::draw_closed_cell(self.position.to_f64(), &c, gl),
+            CellState::Opened => {
+                super::draw::draw_opened_cell(geometry, self.position, &self.content, &c, gl, dd)
+            }
+            CellState::Pressed => {
+                super::draw::draw_pressed_cell(geometry, self.position, &c, gl, dd)
+            }
+            CellState::Flagged(flags) => {
+                super::draw::draw_flagged_cell(geometry, self.position, flags, &c, gl, dd)
+            }
+            CellState::Revealed if self.is_mine() => {
+                super::draw::draw_revealed_cell(geometry, self.position, self.mines(), &c, gl, dd)
+            }
+            CellState::Revealed | CellState::WrongFlag => {
+                super::draw::draw_wrong_flag_cell(geometry, self.position, &c, gl, dd)
+            }
+            _ => super::draw::draw_closed_cell(geometry, self.position, &c, gl, dd),
         }
     }
 
-    fn update(&mut self, _update_args: &UpdateArgs) {}
+    pub fn update(&mut self, update_args: &UpdateArgs) {
+        self.reveal_delay = (self.reveal_delay - update_args.dt).max(0.0);
+    }
 }
@@ -0,0 +1,346 @@
+use std::net::TcpListener;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::cell::CellView;
+use crate::game::net::{
+    self, cell_changes, parse_cell_changes, parse_tokens, player_name, tokens, BoardSpec,
+    CellChange, Connection,
+};
+use crate::game::settings::Settings;
+use crate::game::theme::Color;
+use crate::game::{CellCommand, Game, GameState, Move, Point};
+
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// cursor colours of the players, the host is the first one
+pub const PLAYER_COLORS: [Color; 6] = [
+    [0.9, 0.2, 0.2, 1.0],
+    [0.2, 0.5, 0.95, 1.0],
+    [0.2, 0.75, 0.3, 1.0],
+    [0.95, 0.6, 0.1, 1.0],
+    [0.7, 0.3, 0.85, 1.0],
+    [0.1, 0.75, 0.75, 1.0],
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+struct PlayerCursor {
+    id: u32,
+    name: String,
+    cell: Option<Point<u32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CoopMessage {
+    Join {
+        name: String,
+    },
+    Joined {
+        id: u32,
+        board: BoardSpec,
+    },
+    // `seen` is the cell as the player saw it when making the move
+    Move {
+        command: CellCommand,
+        x: u32,
+        y: u32,
+        seen: String,
+    },
+    NewGame,
+    Cursor {
+        cell: Option<Point<u32>>,
+    },
+    // the whole board, sent to a player who joins and after a new game
+    Board {
+        state: GameState,
+        time: f64,
+        cells: Vec<Vec<String>>,
+    },
+    Cells {
+        state: GameState,
+        time: f64,
+        cells: Vec<CellChange>,
+    },
+    Cursors {
+        players: Vec<PlayerCursor>,
+    },
+}
+
+struct Guest {
+    connection: Connection,
+    cursor: PlayerCursor,
+    // known once the guest has introduced itself
+    joined: bool,
+}
+
+enum Role {
+    Host {
+        listener: TcpListener,
+        board: BoardSpec,
+        guests: Vec<Guest>,
+        next_id: u32,
+        // last state sent to the guests
+        sent: Option<GameState>,
+    },
+    Guest {
+        host: Connection,
+    },
+}
+
+/// Several players on one board. The host owns the field and makes every move, the
+/// guests only send their moves and get back what everybody can see, never the mines.
+pub struct Coop {
+    role: Role,
+    id: u32,
+    name: String,
+    cursor: Option<Point<u32>>,
+    finished: bool,
+}
+
+impl Coop {
+    pub fn host(port: u16, settings: &Settings) -> Result<Coop, String> {
+        let listener = net::listen(port)?;
+        eprintln!("Waiting for players on port {}", port);
+        Ok(Coop {
+            role: Role::Host {
+                listener,
+                board: BoardSpec::new(settings),
+                guests: Vec::new(),
+                next_id: 1,
+                sent: None,
+            },
+            id: 0,
+            name: player_name(settings),
+            cursor: None,
+            finished: false,
+        })
+    }
+
+    /// Connects to the host and replaces the board settings with the ones of the game.
+    pub fn join(address: &str, settings: &mut Settings) -> Result<Coop, String> {
+        let mut host = Connection::connect(address)?;
+        let name = player_name(settings);
+        host.send(&CoopMessage::Join { name: name.clone() });
+        let id = match host.receive_blocking(JOIN_TIMEOUT)? {
+            CoopMessage::Joined { id, board } => {
+                board.apply(settings);
+                id
+            }
+            _ => return Err(format!("{} is not hosting a shared game", address)),
+        };
+        settings
+            .check_board()
+            .map_err(|e| format!("{} has sent a board which can't be played: {}", address, e))?;
+        Ok(Coop {
+            role: Role::Guest { host },
+            id,
+            name,
+            cursor: None,
+            finished: false,
+        })
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        if self.finished {
+            return;
+        }
+        let cursor = game.cursor();
+        let cursor_moved = cursor != self.cursor;
+        self.cursor = cursor;
+        match self.role {
+            Role::Host { .. } => self.update_host(game, cursor_moved),
+            Role::Guest { .. } => self.update_guest(game, cursor_moved),
+        }
+    }
+
+    fn update_host(&mut self, game: &mut Game, mut cursors_changed: bool) {
+        let Role::Host {
+            listener,
+            board,
+            guests,
+            next_id,
+            sent,
+        } = &mut self.role
+        else {
+            return;
+        };
+        for connection in net::accept(listener) {
+            guests.push(Guest {
+                connection,
+                cursor: PlayerCursor {
+                    id: *next_id,
+                    name: String::new(),
+                    cell: None,
+                },
+                joined: false,
+            });
+            *next_id += 1;
+        }
+        let state = game.state();
+        let time = game.time();
+        // moves are made in the order they arrive, a move made on a cell which has changed
+        // since the player saw it is dropped, so two players can't undo each other's flag
+        for guest in guests.iter_mut() {
+            while let Some(message) = guest.connection.receive() {
+                match message {
+                    Ok(CoopMessage::Join { name }) => {
+                        eprintln!("{} has joined the game", name);
+                        guest.cursor.name = name;
+                        guest.joined = true;
+                        guest.connection.send(&CoopMessage::Joined {
+                            id: guest.cursor.id,
+                            board: board.clone(),
+                        });
+                        guest.connection.send(&CoopMessage::Board {
+                            state,
+                            time,
+                            cells: tokens(&game.field().view()),
+                        });
+                        cursors_changed = true;
+                    }
+                    // only players who have joined can change the game
+                    Ok(_) if !guest.joined => eprintln!(
+                        "{} has sent a message before joining",
+                        guest.connection.peer()
+                    ),
+                    Ok(CoopMessage::Move {
+                        command,
+                        x,
+                        y,
+                        seen,
+                    }) => {
+                        let cell = Point { x, y };
+                        let current = game
+                            .field()
+                            .contains(cell)
+                            .then(|| game.field().cell(cell).view());
+                        if current.is_some() && current == CellView::parse(&seen) {
+                            game.command(command, cell);
+                        } else if current.is_some() {
+                            // the player gets the current cell to try again
+                            guest.connection.send(&CoopMessage::Cells {
+                                state,
+                                time,
+                                cells: cell_changes(game.field(), &[cell]),
+                            });
+                        }
+                    }
+                    Ok(CoopMessage::NewGame) => game.new_game(),
+                    Ok(CoopMessage::Cursor { cell }) => {
+                        guest.cursor.cell = cell;
+                        cursors_changed = true;
+                    }
+                    Ok(_) => eprintln!("Unexpected message from {}", guest.connection.peer()),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+        guests.retain(|guest| {
+            if guest.connection.is_closed() {
+                if guest.joined {
+                    eprintln!("{} has left the game", guest.cursor.name);
+                }
+                cursors_changed = true;
+            }
+            !guest.connection.is_closed()
+        });
+        // moves made above are in the changes of the next update
+        let changes = game.changes();
+        let message = match changes.cells() {
+            None => Some(CoopMessage::Board {
+                state,
+                time,
+                cells: tokens(&game.field().view()),
+            }),
+            Some(cells) if !changes.is_empty() || *sent != Some(state) => {
+                Some(CoopMessage::Cells {
+                    state,
+                    time,
+                    cells: cell_changes(game.field(), cells),
+                })
+            }
+            Some(_) => None,
+        };
+        if let Some(message) = message {
+            for guest in guests.iter_mut().filter(|guest| guest.joined) {
+                guest.connection.send(&message);
+            }
+            *sent = Some(state);
+        }
+        if cursors_changed {
+            let mut players = vec![PlayerCursor {
+                id: self.id,
+                name: self.name.clone(),
+                cell: self.cursor,
+            }];
+            players.extend(
+                guests
+                    .iter()
+                    .filter(|guest| guest.joined)
+                    .map(|guest| guest.cursor.clone()),
+            );
+            let message = CoopMessage::Cursors {
+                players: players.clone(),
+            };
+            for guest in guests.iter_mut().filter(|guest| guest.joined) {
+                guest.connection.send(&message);
+            }
+            game.set_cursors(other_cursors(&players, self.id));
+        }
+    }
+
+    fn update_guest(&mut self, game: &mut Game, cursor_moved: bool) {
+        let Role::Guest { host } = &mut self.role else {
+            return;
+        };
+        for made in game.take_moves() {
+            match made {
+                Move::Cell(command, cell) => host.send(&CoopMessage::Move {
+                    command,
+                    x: cell.x,
+                    y: cell.y,
+                    seen: game.field().cell(cell).view().token(),
+                }),
+                Move::NewGame => host.send(&CoopMessage::NewGame),
+            }
+        }
+        if cursor_moved {
+            host.send(&CoopMessage::Cursor { cell: self.cursor });
+        }
+        while let Some(message) = host.receive() {
+            match message {
+                Ok(CoopMessage::Board { state, time, cells }) => match parse_tokens(&cells) {
+                    Some(view) => game.show_board(state, time, &view),
+                    None => eprintln!("Host has sent a board which can't be read"),
+                },
+                Ok(CoopMessage::Cells { state, time, cells }) => {
+                    game.show_state(state, time);
+                    game.show_cells(&parse_cell_changes(&cells));
+                }
+                Ok(CoopMessage::Cursors { players }) => {
+                    game.set_cursors(other_cursors(&players, self.id));
+                }
+                Ok(_) => eprintln!("Unexpected message from the host"),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if host.is_closed() {
+            eprintln!("Host has closed the game");
+            self.finished = true;
+            game.set_cursors(Vec::new());
+        }
+    }
+}
+
+fn other_cursors(players: &[PlayerCursor], own_id: u32) -> Vec<(Point<u32>, Color)> {
+    players
+        .iter()
+        .filter(|player| player.id != own_id)
+        .filter_map(|player| {
+            let color = PLAYER_COLORS[player.id as usize % PLAYER_COLORS.len()];
+            Some((player.cell?, color))
+        })
+        .collect()
+}
@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::assets;
+use crate::game::field::{FIELD_SIZE_10, FIELD_SIZE_40, FIELD_SIZE_99};
+use crate::game::geometry::GridKind;
+use crate::game::settings::Settings;
+use crate::game::{Deal, Game, GameState, Point};
+
+const RECORDS_FILE: &str = "daily.json";
+// length of the progress bar in the result string
+const BAR_LENGTH: u32 = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Unfinished,
+    Won,
+    Lost,
+}
+
+/// The ranked attempt of a day, kept in `daily.json` in the data directory.
+#[derive(Clone, Serialize, Deserialize)]
+struct Attempt {
+    date: String,
+    difficulty: String,
+    outcome: Outcome,
+    time: f64,
+    // percent of the safe cells opened
+    cleared: u32,
+}
+
+impl Attempt {
+    fn summary(&self) -> String {
+        match self.outcome {
+            // the progress of an attempt left by quitting the game isn't known
+            Outcome::Unfinished if self.time == 0.0 => String::from("unfinished"),
+            Outcome::Unfinished => format!("gave up at {}% in {:.1}s", self.cleared, self.time),
+            Outcome::Won => format!("won in {:.1}s", self.time),
+            Outcome::Lost => format!("lost at {}% in {:.1}s", self.cleared, self.time),
+        }
+    }
+
+    /// One line which can be pasted into a chat, like
+    /// `Rust Minesweeper daily 2024-05-01 expert 🟩🟩🟩🟩💥⬛⬛⬛⬛⬛ lost at 43% in 52.0s`.
+    fn share(&self) -> String {
+        let opened = (self.cleared * BAR_LENGTH / 100).min(BAR_LENGTH);
+        let mut bar = "🟩".repeat(opened as usize);
+        let mut rest = BAR_LENGTH - opened;
+        if self.outcome == Outcome::Lost && rest > 0 {
+            bar.push('💥');
+            rest -= 1;
+        }
+        bar.push_str(&"⬛".repeat(rest as usize));
+        format!(
+            "Rust Minesweeper daily {} {} {} {}",
+            self.date,
+            self.difficulty,
+            bar,
+            self.summary()
+        )
+    }
+}
+
+/// Everybody gets the same board for a day and a difficulty. The first game of the day
+/// is the ranked attempt, recorded as soon as it starts so quitting doesn't give another
+/// try, later games on the board are practice.
+pub struct Daily {
+    date: String,
+    difficulty: String,
+    path: Option<PathBuf>,
+    attempt: Option<Attempt>,
+    // the game being played is the ranked attempt
+    ranked: bool,
+    state: GameState,
+}
+
+impl Daily {
+    pub fn new(settings: &Settings) -> Daily {
+        let date = today();
+        let difficulty = difficulty(settings);
+        let path = assets::data_dir().map(|dir| dir.join(RECORDS_FILE));
+        let attempt = load(path.as_ref())
+            .into_iter()
+            .find(|attempt| attempt.date == date && attempt.difficulty == difficulty);
+        eprintln!("Daily challenge {} {}", date, difficulty);
+        if let Some(attempt) = &attempt {
+            eprintln!("The ranked attempt is over, games are practice");
+            println!("{}", attempt.share());
+        }
+        Daily {
+            date,
+            difficulty,
+            path,
+            attempt,
+            ranked: false,
+            state: GameState::Ready,
+        }
+    }
+
+    /// Deals the board of the day, opened in the middle like a race.
+    pub fn deal(&self, game: &mut Game) {
+        let size = game.field().size();
+        let first_cell = Point {
+            x: size.width / 2,
+            y: size.height / 2,
+        };
+        let seed = seed(&self.date, &self.difficulty);
+        game.set_deal(Some(Deal::Seeded(seed, first_cell)));
+        game.set_scoreboard(self.scoreboard());
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        let state = game.state();
+        // a new game resets the board, so the progress is kept while playing
+        if let Some(attempt) = self.attempt.as_mut().filter(|_| self.ranked) {
+            if state != GameState::Ready {
+                attempt.time = game.time();
+                attempt.cleared = (game.field().cleared() * 100.0).floor() as u32;
+            }
+        }
+        if state == self.state {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.state, state);
+        match state {
+            GameState::Playing if previous == GameState::Ready && self.attempt.is_none() => {
+                self.ranked = true;
+                self.attempt = Some(Attempt {
+                    date: self.date.clone(),
+                    difficulty: self.difficulty.clone(),
+                    outcome: Outcome::Unfinished,
+                    time: 0.0,
+                    cleared: 0,
+                });
+                self.save();
+            }
+            GameState::Win | GameState::Loose | GameState::Ready if self.ranked => {
+                self.ranked = false;
+                if let Some(attempt) = &mut self.attempt {
+                    attempt.outcome = match state {
+                        GameState::Win => Outcome::Won,
+                        GameState::Loose => Outcome::Lost,
+                        _ => Outcome::Unfinished,
+                    };
+                    println!("{}", attempt.share());
+                }
+                self.save();
+            }
+            _ => {}
+        }
+        game.set_scoreboard(self.scoreboard());
+    }
+
+    fn scoreboard(&self) -> Vec<String> {
+        let status = match (&self.attempt, self.ranked) {
+            (None, _) => String::from("Ranked attempt, the clock starts with the first move"),
+            (Some(_), true) => String::from("Ranked attempt"),
+            (Some(attempt), false) => format!("Practice, ranked attempt {}", attempt.summary()),
+        };
+        vec![format!("Daily {} {}", self.date, self.difficulty), status]
+    }
+
+    // other days and difficulties are kept as they are
+    fn save(&self) {
+        let (Some(path), Some(attempt)) = (&self.path, &self.attempt) else {
+            return;
+        };
+        let mut attempts = load(Some(path));
+        attempts
+            .retain(|other| other.date != attempt.date || other.difficulty != attempt.difficulty);
+        attempts.push(attempt.clone());
+        let json = serde_json::to_string_pretty(&attempts).expect("Attempts should be serialized");
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, json));
+        if let Err(e) = saved {
+            eprintln!("Can't save the daily attempt to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn load(path: Option<&PathBuf>) -> Vec<Attempt> {
+    let Some(json) = path.and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("Can't read the daily attempts: {}", e);
+        Vec::new()
+    })
+}
+
+// named sizes, or the custom size, and the grid unless it's square
+fn difficulty(settings: &Settings) -> String {
+    let size = settings.field_size;
+    let mut name = match size {
+        _ if size == FIELD_SIZE_10 => String::from("beginner"),
+        _ if size == FIELD_SIZE_40 => String::from("intermediate"),
+        _ if size == FIELD_SIZE_99 => String::from("expert"),
+        _ => format!("{}x{}x{}", size.width, size.height, size.mines),
+    };
+    if settings.grid != GridKind::Square {
+        name = format!("{} {}", name, settings.grid.name());
+    }
+    name
+}
+
+// FNV-1a, unlike the hasher of the standard library it's the same on every build
+fn seed(date: &str, difficulty: &str) -> u64 {
+    format!("{} {}", date, difficulty)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+// the date in UTC, so the whole team gets the same board wherever they are
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    date(seconds)
+}
+
+fn date(seconds: u64) -> String {
+    // days to the civil date, http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_of_known_days() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(86_399), "1970-01-01");
+        assert_eq!(date(946_598_400), "1999-12-31");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(951_868_800), "2000-03-01");
+        assert_eq!(date(1_709_164_800), "2024-02-29");
+    }
+
+    // a different seed would deal everybody a different board of the day
+    #[test]
+    fn seed_is_fixed() {
+        assert_eq!(seed("2024-05-01", "expert"), 2_264_035_721_169_307_543);
+        assert_ne!(seed("2024-05-01", "expert"), seed("2024-05-02", "expert"));
+        assert_ne!(seed("2024-05-01", "expert"), seed("2024-05-01", "beginner"));
+    }
+}
@@ -1,14 +1,44 @@
-use graphics::color::WHITE;
+use graphics::character::CharacterCache;
 use graphics::math::Matrix2d;
 use graphics::{Context, Transformed};
 use opengl_graphics::{GlGraphics, GlyphCache};
 
 use crate::game::cell::CellContent;
+use crate::game::geometry::{Geometry, GridKind};
+use crate::game::skin::Skin;
+use crate::game::theme::{Color, Theme};
 use crate::game::Point;
-use crate::{TextureSettings, CELL_SIZE};
+
+const PAUSED: &str = "Paused";
 
 pub struct DrawData<'a> {
     pub glyph_cache: GlyphCache<'a>,
+    pub theme: Theme,
+    pub skin: Option<Skin>,
+    themes: Vec<Theme>,
+    theme_index: usize,
+}
+
+impl<'a> DrawData<'a> {
+    pub fn new(
+        glyph_cache: GlyphCache<'a>,
+        themes: Vec<Theme>,
+        theme_index: usize,
+        skin: Option<Skin>,
+    ) -> DrawData<'a> {
+        DrawData {
+            glyph_cache,
+            theme: themes[theme_index].clone(),
+            skin,
+            themes,
+            theme_index,
+        }
+    }
+
+    pub fn next_theme(&mut self) {
+        self.theme_index = (self.theme_index + 1) % self.themes.len();
+        self.theme = self.themes[self.theme_index].clone();
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -38,40 +68,10 @@ impl Rect {
         }
     }
 
-    pub fn from_arr(arr: [f64; 4]) -> Rect {
-        Rect::new(arr[0], arr[1], arr[2], arr[3])
-    }
-
-    pub fn from_arr_u(arr: [u32; 4]) -> Rect {
-        Rect::new(arr[0] as f64, arr[1] as f64, arr[2] as f64, arr[3] as f64)
-    }
-
-    pub fn from_cell_point_f64(point: Point<f64>) -> Rect {
-        Rect::new(
-            point.x * CELL_SIZE,
-            point.y * CELL_SIZE,
-            CELL_SIZE,
-            CELL_SIZE,
-        )
-    }
-
-    pub fn from_cell_point_u32(point: Point<u32>) -> Rect {
-        Rect::new(
-            point.x as f64 * CELL_SIZE,
-            point.y as f64 * CELL_SIZE,
-            CELL_SIZE,
-            CELL_SIZE,
-        )
-    }
-
-    pub fn to_arr(&self) -> [f64; 4] {
+    pub fn to_arr(self) -> [f64; 4] {
         [self.x, self.y, self.width, self.height]
     }
 
-    pub fn coord_arr(&self) -> [f64; 2] {
-        [self.x, self.y]
-    }
-
     pub fn contains_point(&self, point: Point<f64>) -> bool {
         point.x >= self.x
             && point.x <= self.x + self.width
@@ -80,40 +80,13 @@ impl Rect {
     }
 }
 
-const CELL_BLUE: [f32; 4] = [0.0, 0.6352941176470588, 0.9098039215686275, 1.0];
-const CELL_PRESSED_BLUE: [f32; 4] = [0.0, 150.0 / 255.0, 219.0 / 255.0, 1.0];
-const LIGHT_BLUE: [f32; 4] = [0.6, 0.8509803921568627, 0.9176470588235294, 1.0];
-const DARK_BLUE: [f32; 4] = [
-    0.2588235294117647,
-    0.392156862745098,
-    0.5764705882352941,
-    1.0,
-];
-const GRAY: [f32; 4] = [
-    0.7647058823529412,
-    0.7647058823529412,
-    0.7647058823529412,
-    1.0,
-];
-const DARK_RED: [f32; 4] = [0.5333333333333333, 0.0, 0.0823529411764706, 1.0];
-
-const TEXT_COLORS: [[f32; 4]; 8] = [
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-    [0.0, 0.0, 0.0, 1.0],
-];
-
+#[allow(clippy::too_many_arguments)]
 fn draw_bordered_square_0(
-    color: [f32; 4],
-    top_color: [f32; 4],
-    right_color: [f32; 4],
-    left_color: [f32; 4],
-    bottom_color: [f32; 4],
+    color: Color,
+    top_color: Color,
+    right_color: Color,
+    left_color: Color,
+    bottom_color: Color,
     rect: Rect,
     transform: Matrix2d,
     gl: &mut GlGraphics,
@@ -153,182 +126,535 @@ fn draw_bordered_square_0(
     );
 }
 
-fn draw_closed_cell_0(position: Point<f64>, transform: Matrix2d, gl: &mut GlGraphics) {
-    draw_bordered_square_0(
-        CELL_BLUE,
-        LIGHT_BLUE,
-        LIGHT_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        Rect {
-            x: position.x * CELL_SIZE,
-            y: position.y * CELL_SIZE,
-            width: CELL_SIZE,
-            height: CELL_SIZE,
-        },
-        transform,
-        gl,
-    );
+// lit edges face up or right, shaded edges face down or left
+fn draw_bordered_polygon_0(
+    color: Color,
+    lit_color: Color,
+    shaded_color: Color,
+    outline: &[[f64; 2]],
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    graphics::polygon(color, outline, transform, gl);
+    for i in 0..outline.len() {
+        let from = outline[i];
+        let to = outline[(i + 1) % outline.len()];
+        // outline goes clockwise, so the outer normal is (dy, -dx)
+        let normal_x = to[1] - from[1];
+        let normal_y = from[0] - to[0];
+        let edge_color =
+            if normal_y < -f64::EPSILON || (normal_y.abs() <= f64::EPSILON && normal_x > 0.0) {
+                lit_color
+            } else {
+                shaded_color
+            };
+        graphics::line_from_to(edge_color, 0.5, from, to, transform, gl);
+    }
+}
+
+fn draw_cell_0(
+    geometry: &Geometry,
+    position: Point<u32>,
+    color: Color,
+    lit_color: Color,
+    shaded_color: Color,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    match geometry.kind() {
+        GridKind::Square => draw_bordered_square_0(
+            color,
+            lit_color,
+            lit_color,
+            shaded_color,
+            shaded_color,
+            geometry.cell_bounds(position),
+            transform,
+            gl,
+        ),
+        GridKind::Hexagonal | GridKind::Triangular => draw_bordered_polygon_0(
+            color,
+            lit_color,
+            shaded_color,
+            &geometry.cell_outline(position),
+            transform,
+            gl,
+        ),
+    }
+}
+
+fn draw_sprite_0(
+    name: &str,
+    rect: Rect,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) -> bool {
+    dd.skin
+        .as_ref()
+        .is_some_and(|skin| skin.draw(name, rect, transform, gl))
+}
+
+// sprites are square, so other tilings keep the vector cells
+fn draw_cell_sprite_0(
+    geometry: &Geometry,
+    position: Point<u32>,
+    name: &str,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) -> bool {
+    geometry.kind() == GridKind::Square
+        && draw_sprite_0(name, geometry.cell_bounds(position), transform, gl, dd)
+}
+
+// display digits are drawn only when the skin has every one of them
+fn draw_display_sprites_0(
+    text: &str,
+    rect: Rect,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) -> bool {
+    let skin = match &dd.skin {
+        Some(skin) => skin,
+        None => return false,
+    };
+    let names: Vec<String> = text.chars().map(|c| format!("digit_{}", c)).collect();
+    let aspects: Option<Vec<f64>> = names.iter().map(|name| skin.sprite_aspect(name)).collect();
+    let aspects = match aspects {
+        Some(aspects) => aspects,
+        None => return false,
+    };
+    let height = rect.height * 0.8;
+    let width: f64 = aspects.iter().map(|aspect| aspect * height).sum();
+    let mut x = rect.x + (rect.width - width) / 2.0;
+    let y = rect.y + (rect.height - height) / 2.0;
+    for (name, aspect) in names.iter().zip(aspects) {
+        skin.draw(
+            name,
+            Rect::new(x, y, aspect * height, height),
+            transform,
+            gl,
+        );
+        x += aspect * height;
+    }
+    true
 }
 
-fn draw_flag_0(position: Point<f64>, gl: &mut GlGraphics, transform: Matrix2d) {
+// proportions are taken from the original 30px cell
+fn draw_flag_0(bounds: Rect, theme: &Theme, gl: &mut GlGraphics, transform: Matrix2d) {
+    let w = bounds.width / 30.0;
+    let h = bounds.height / 30.0;
+    let pole_x = bounds.x + bounds.width / 2.0 - 3.0 * w;
     graphics::line_from_to(
-        GRAY,
-        1.0,
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0) as f64,
-            (position.y * CELL_SIZE + 5.0) as f64,
-        ],
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0) as f64,
-            (position.y * CELL_SIZE + CELL_SIZE - 5.0) as f64,
-        ],
+        theme.flag_pole,
+        w,
+        [pole_x, bounds.y + 5.0 * h],
+        [pole_x, bounds.y + bounds.height - 5.0 * h],
         transform,
         gl,
     );
     graphics::line_from_to(
-        DARK_RED,
-        1.0,
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0 + 1.0) as f64,
-            (position.y * CELL_SIZE + 5.0) as f64,
-        ],
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0 + 10.0) as f64,
-            (position.y * CELL_SIZE + 11.0) as f64,
-        ],
+        theme.flag,
+        w,
+        [pole_x + w, bounds.y + 5.0 * h],
+        [pole_x + 10.0 * w, bounds.y + 11.0 * h],
         transform,
         gl,
     );
     graphics::line_from_to(
-        DARK_RED,
-        1.0,
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0 + 10.0) as f64,
-            (position.y * CELL_SIZE + 11.0) as f64,
-        ],
-        [
-            (position.x * CELL_SIZE + CELL_SIZE / 2.0 - 3.0 + 1.0) as f64,
-            (position.y * CELL_SIZE + 18.0) as f64,
-        ],
+        theme.flag,
+        w,
+        [pole_x + 10.0 * w, bounds.y + 11.0 * h],
+        [pole_x + w, bounds.y + 18.0 * h],
         transform,
         gl,
     );
 }
 
-pub fn draw_pressed_cell(position: Point<f64>, c: &Context, gl: &mut GlGraphics) {
-    draw_bordered_square_0(
-        CELL_PRESSED_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        LIGHT_BLUE,
-        LIGHT_BLUE,
-        Rect {
-            x: position.x * CELL_SIZE,
-            y: position.y * CELL_SIZE,
-            width: CELL_SIZE,
-            height: CELL_SIZE,
-        },
+pub fn draw_pressed_cell(
+    geometry: &Geometry,
+    position: Point<u32>,
+    c: &Context,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) {
+    if draw_cell_sprite_0(geometry, position, "pressed", c.transform, gl, dd) {
+        return;
+    }
+    let theme = &dd.theme;
+    draw_cell_0(
+        geometry,
+        position,
+        theme.cell_pressed,
+        theme.cell_dark,
+        theme.cell_light,
         c.transform,
         gl,
     );
 }
 
-pub fn draw_closed_cell(position: Point<f64>, c: &Context, gl: &mut GlGraphics) {
-    draw_closed_cell_0(position, c.transform, gl)
+pub fn draw_closed_cell(
+    geometry: &Geometry,
+    position: Point<u32>,
+    c: &Context,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) {
+    if draw_cell_sprite_0(geometry, position, "closed", c.transform, gl, dd) {
+        return;
+    }
+    let theme = &dd.theme;
+    draw_cell_0(
+        geometry,
+        position,
+        theme.cell,
+        theme.cell_light,
+        theme.cell_dark,
+        c.transform,
+        gl,
+    );
 }
 
-pub fn draw_flagged_cell(position: Point<f64>, c: &Context, gl: &mut GlGraphics) {
-    draw_closed_cell_0(position, c.transform, gl);
-    draw_flag_0(position, gl, c.transform);
+pub fn draw_flagged_cell(
+    geometry: &Geometry,
+    position: Point<u32>,
+    flags: u8,
+    c: &Context,
+    gl: &mut GlGraphics,
+    dd: &mut DrawData,
+) {
+    let bounds = geometry.cell_bounds(position);
+    if !draw_cell_sprite_0(geometry, position, "flag", c.transform, gl, dd) {
+        draw_closed_cell(geometry, position, c, gl, dd);
+        draw_flag_0(bounds, &dd.theme, gl, c.transform);
+    }
+    if flags > 1 {
+        let color = dd.theme.flag;
+        draw_multiplier_0(bounds, color, gl, &mut dd.glyph_cache, c.transform, flags);
+    }
 }
 
 pub fn draw_opened_cell(
-    position: Point<f64>,
+    geometry: &Geometry,
+    position: Point<u32>,
     content: &CellContent,
     c: &Context,
     gl: &mut GlGraphics,
-    glyph_cache: &mut GlyphCache,
+    dd: &mut DrawData,
 ) {
     let transform = c.transform;
-    draw_bordered_square_0(
-        GRAY,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        Rect::from_cell_point_f64(position),
-        transform,
-        gl,
-    );
+    draw_opened_background_0(geometry, position, transform, gl, dd);
+    let bounds = geometry.cell_bounds(position);
     match content {
-        CellContent::Mine => {
-            draw_mine_0(position, gl, transform);
+        CellContent::Mine(mines) => {
+            let color = dd.theme.mine;
+            // mines are opened only by clicking them, the rest are revealed after the game
+            if !draw_cell_sprite_0(geometry, position, "exploded_mine", transform, gl, dd) {
+                let theme = &dd.theme;
+                draw_cell_0(
+                    geometry,
+                    position,
+                    theme.exploded,
+                    theme.opened_border,
+                    theme.opened_border,
+                    transform,
+                    gl,
+                );
+                draw_mine_0(bounds, color, gl, transform);
+            }
+            if *mines > 1 {
+                draw_multiplier_0(bounds, color, gl, &mut dd.glyph_cache, transform, *mines);
+            }
         }
         CellContent::Number(number) => {
-            draw_number_0(position, gl, glyph_cache, transform, number);
+            let sprite = format!("number_{}", number);
+            if !draw_cell_sprite_0(geometry, position, &sprite, transform, gl, dd) {
+                let color = dd.theme.number(*number);
+                draw_number_0(bounds, color, gl, &mut dd.glyph_cache, transform, number);
+            }
         }
         _ => {} //nothing
     }
 }
 
+/// Mine shown after the game is lost without being clicked.
+pub fn draw_revealed_cell(
+    geometry: &Geometry,
+    position: Point<u32>,
+    mines: u8,
+    c: &Context,
+    gl: &mut GlGraphics,
+    dd: &mut DrawData,
+) {
+    let transform = c.transform;
+    draw_opened_background_0(geometry, position, transform, gl, dd);
+    let bounds = geometry.cell_bounds(position);
+    let color = dd.theme.mine;
+    if !draw_cell_sprite_0(geometry, position, "mine", transform, gl, dd) {
+        draw_mine_0(bounds, color, gl, transform);
+    }
+    if mines > 1 {
+        draw_multiplier_0(bounds, color, gl, &mut dd.glyph_cache, transform, mines);
+    }
+}
+
+/// Flag which turned out to have no mine under it, shown after the game is lost.
+pub fn draw_wrong_flag_cell(
+    geometry: &Geometry,
+    position: Point<u32>,
+    c: &Context,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) {
+    let transform = c.transform;
+    if draw_cell_sprite_0(geometry, position, "wrong_flag", transform, gl, dd) {
+        return;
+    }
+    draw_opened_background_0(geometry, position, transform, gl, dd);
+    let bounds = geometry.cell_bounds(position);
+    draw_mine_0(bounds, dd.theme.mine, gl, transform);
+    let (left, top) = (
+        bounds.x + bounds.width * 0.2,
+        bounds.y + bounds.height * 0.2,
+    );
+    let (right, bottom) = (
+        bounds.x + bounds.width * 0.8,
+        bounds.y + bounds.height * 0.8,
+    );
+    let width = bounds.width / 15.0;
+    graphics::line_from_to(
+        dd.theme.flag,
+        width,
+        [left, top],
+        [right, bottom],
+        transform,
+        gl,
+    );
+    graphics::line_from_to(
+        dd.theme.flag,
+        width,
+        [left, bottom],
+        [right, top],
+        transform,
+        gl,
+    );
+}
+
+fn draw_opened_background_0(
+    geometry: &Geometry,
+    position: Point<u32>,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+    dd: &DrawData,
+) {
+    if !draw_cell_sprite_0(geometry, position, "opened", transform, gl, dd) {
+        let theme = &dd.theme;
+        draw_cell_0(
+            geometry,
+            position,
+            theme.opened,
+            theme.opened_border,
+            theme.opened_border,
+            transform,
+            gl,
+        );
+    }
+}
+
+/// Highlight of the cell under the cursor.
+pub fn draw_hover(
+    geometry: &Geometry,
+    position: Point<u32>,
+    theme: &Theme,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    let mut color = theme.cell_light;
+    color[3] *= 0.4;
+    graphics::polygon(color, &geometry.cell_outline(position), transform, gl);
+}
+
+// cursor of another player sharing the board
+pub fn draw_cursor(
+    geometry: &Geometry,
+    position: Point<u32>,
+    color: Color,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    let outline = geometry.cell_outline(position);
+    let mut fill = color;
+    fill[3] *= 0.3;
+    graphics::polygon(fill, &outline, transform, gl);
+    for (i, from) in outline.iter().enumerate() {
+        let to = outline[(i + 1) % outline.len()];
+        graphics::line_from_to(color, 1.5, *from, to, transform, gl);
+    }
+}
+
+/// Shock wave from the mine which was hit, progress goes from 0.0 to 1.0.
+pub fn draw_explosion(
+    center: Point<f64>,
+    max_radius: f64,
+    progress: f64,
+    theme: &Theme,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    let radius = max_radius * progress;
+    let mut color = theme.mine;
+    color[3] *= (1.0 - progress) as f32;
+    graphics::circle_arc(
+        color,
+        max_radius / 10.0,
+        0.0,
+        std::f64::consts::TAU,
+        [
+            center.x - radius,
+            center.y - radius,
+            radius * 2.0,
+            radius * 2.0,
+        ],
+        transform,
+        gl,
+    );
+}
+
+/// Rings spreading over the field after a win, progress goes from 0.0 to 1.0.
+pub fn draw_celebration(
+    rect: Rect,
+    progress: f64,
+    theme: &Theme,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    const RINGS: u32 = 3;
+    let center = [rect.x + rect.width / 2.0, rect.y + rect.height / 2.0];
+    let max_radius = rect.width.hypot(rect.height) / 2.0;
+    for ring in 0..RINGS {
+        let ring_progress = progress * 2.0 - ring as f64 / RINGS as f64;
+        if !(0.0..=1.0).contains(&ring_progress) {
+            continue;
+        }
+        let radius = max_radius * ring_progress;
+        let mut color = theme.number(ring as u8 + 1);
+        color[3] *= (1.0 - ring_progress) as f32;
+        graphics::circle_arc(
+            color,
+            max_radius / 30.0,
+            0.0,
+            std::f64::consts::TAU,
+            [
+                center[0] - radius,
+                center[1] - radius,
+                radius * 2.0,
+                radius * 2.0,
+            ],
+            transform,
+            gl,
+        );
+    }
+}
+
 fn draw_number_0(
-    position: Point<f64>,
+    bounds: Rect,
+    color: Color,
     gl: &mut GlGraphics,
     glyph_cache: &mut GlyphCache,
     transform: Matrix2d,
     number: &u8,
+) {
+    let text = number.to_string();
+    let font_size = (bounds.height * 0.7) as u32;
+    // kernels and tilings with more than 8 neighbours produce two digit numbers
+    let text_width = glyph_cache
+        .width(font_size, text.as_str())
+        .unwrap_or(bounds.width / 2.0);
+    graphics::text(
+        color,
+        font_size,
+        text.as_str(),
+        glyph_cache,
+        transform.trans(
+            bounds.x + (bounds.width - text_width) / 2.0,
+            bounds.y + (bounds.height / 5.0 * 4.0),
+        ),
+        gl,
+    )
+    .expect("Cell text should be rendered");
+}
+
+// small count in the bottom right corner of cells holding several mines or flags
+fn draw_multiplier_0(
+    bounds: Rect,
+    color: Color,
+    gl: &mut GlGraphics,
+    glyph_cache: &mut GlyphCache,
+    transform: Matrix2d,
+    count: u8,
 ) {
     graphics::text(
-        TEXT_COLORS[(number - 1) as usize],
-        (CELL_SIZE as f64 * 0.7) as u32,
-        number.to_string().as_str(),
+        color,
+        (bounds.height * 0.4) as u32,
+        count.to_string().as_str(),
         glyph_cache,
         transform.trans(
-            position.x * CELL_SIZE + (CELL_SIZE / 4.0),
-            position.y * CELL_SIZE + (CELL_SIZE / 5.0 * 4.0),
+            bounds.x + bounds.width * 0.65,
+            bounds.y + bounds.height * 0.9,
         ),
         gl,
     )
     .expect("Cell text should be rendered");
 }
 
-fn draw_mine_0(position: Point<f64>, gl: &mut GlGraphics, transform: Matrix2d) {
+fn draw_mine_0(bounds: Rect, color: Color, gl: &mut GlGraphics, transform: Matrix2d) {
     graphics::circle_arc(
-        DARK_RED,
+        color,
         1.0,
         0.0,
         360.0,
-        [2.0, 2.0, (CELL_SIZE - 4.0) as f64, (CELL_SIZE - 4.0) as f64],
-        transform.trans(position.x * CELL_SIZE, position.y * CELL_SIZE),
+        [
+            bounds.width / 15.0,
+            bounds.height / 15.0,
+            bounds.width * 13.0 / 15.0,
+            bounds.height * 13.0 / 15.0,
+        ],
+        transform.trans(bounds.x, bounds.y),
         gl,
     );
 }
 
 pub fn draw_menu_button(
     text: &str,
+    sprite: &str,
     rect: Rect,
     c: Context,
     gl: &mut GlGraphics,
     dd: &mut DrawData,
 ) {
+    if draw_sprite_0(sprite, rect, c.transform, gl, dd) {
+        return;
+    }
+    let theme = &dd.theme;
     draw_bordered_square_0(
-        CELL_BLUE,
-        LIGHT_BLUE,
-        LIGHT_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
+        theme.cell,
+        theme.cell_light,
+        theme.cell_light,
+        theme.cell_dark,
+        theme.cell_dark,
         rect,
         c.transform,
         gl,
     );
     graphics::text(
-        WHITE,
+        theme.button_text,
         (rect.height * 0.6) as u32,
         text,
         &mut dd.glyph_cache,
         c.transform
-            .trans(rect.x + 3.0, rect.y + rect.height * 0.6 + 3.0),
+            .trans(rect.x + rect.height * 0.075, rect.y + rect.height * 0.675),
         gl,
     )
     .expect("Button text should be rendered");
@@ -341,68 +667,330 @@ pub fn draw_menu_button_pressed(
     gl: &mut GlGraphics,
     dd: &mut DrawData,
 ) {
+    if draw_sprite_0("face_pressed", rect, c.transform, gl, dd) {
+        return;
+    }
+    let theme = &dd.theme;
     draw_bordered_square_0(
-        CELL_PRESSED_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        LIGHT_BLUE,
-        LIGHT_BLUE,
+        theme.cell_pressed,
+        theme.cell_dark,
+        theme.cell_dark,
+        theme.cell_light,
+        theme.cell_light,
         rect,
         c.transform,
         gl,
     );
     graphics::text(
-        WHITE,
+        theme.button_text,
         (rect.height * 0.6) as u32,
         text,
         &mut dd.glyph_cache,
         c.transform
-            .trans(rect.x + 3.0, rect.y + rect.height * 0.6 + 3.0),
+            .trans(rect.x + rect.height * 0.075, rect.y + rect.height * 0.675),
         gl,
     )
     .expect("Button text should be rendered");
 }
 
-pub fn draw_timer(rect: Rect, sec: f64, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
+pub fn draw_timer(
+    rect: Rect,
+    sec: f64,
+    paused: bool,
+    c: Context,
+    gl: &mut GlGraphics,
+    dd: &mut DrawData,
+) {
+    let theme = &dd.theme;
     draw_bordered_square_0(
-        LIGHT_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
+        if paused {
+            theme.display_paused
+        } else {
+            theme.display
+        },
+        theme.cell_dark,
+        theme.cell_dark,
+        theme.cell_dark,
+        theme.cell_dark,
         rect,
         c.transform,
         gl,
     );
+    let text = format!("{:0>3.0}", sec);
+    if draw_display_sprites_0(&text, rect, c.transform, gl, dd) {
+        return;
+    }
     graphics::text(
-        WHITE,
+        dd.theme.display_text,
         (rect.height * 0.8) as u32,
-        format!("{:0>3.0}", sec).as_str(),
+        text.as_str(),
         &mut dd.glyph_cache,
-        c.transform.trans(rect.x + 20.0, rect.y + rect.height * 0.8),
+        c.transform
+            .trans(rect.x + rect.width * 0.2, rect.y + rect.height * 0.8),
         gl,
     )
     .expect("Cell text should be rendered");
 }
 
+pub fn draw_pause_cover(rect: Rect, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
+    let theme = &dd.theme;
+    draw_bordered_square_0(
+        theme.cell,
+        theme.cell_light,
+        theme.cell_light,
+        theme.cell_dark,
+        theme.cell_dark,
+        rect,
+        c.transform,
+        gl,
+    );
+    let text_color = theme.button_text;
+    let font_size = (rect.width.min(rect.height) * 0.12) as u32;
+    let text_width = dd
+        .glyph_cache
+        .width(font_size, PAUSED)
+        .unwrap_or(rect.width / 2.0);
+    graphics::text(
+        text_color,
+        font_size,
+        PAUSED,
+        &mut dd.glyph_cache,
+        c.transform.trans(
+            rect.x + (rect.width - text_width) / 2.0,
+            rect.y + rect.height / 2.0,
+        ),
+        gl,
+    )
+    .expect("Pause text should be rendered");
+}
+
+/// Lines of text in a box, like the opponents of a race. `line_height` scales with the menu.
+pub fn draw_scoreboard(
+    lines: &[String],
+    position: Point<f64>,
+    line_height: f64,
+    c: Context,
+    gl: &mut GlGraphics,
+    dd: &mut DrawData,
+) {
+    let font_size = (line_height * 0.7) as u32;
+    let padding = line_height * 0.3;
+    let text_width = lines
+        .iter()
+        .map(|line| dd.glyph_cache.width(font_size, line).unwrap_or(0.0))
+        .fold(0.0, f64::max);
+    let rect = Rect::new(
+        position.x,
+        position.y,
+        text_width + padding * 2.0,
+        line_height * lines.len() as f64 + padding * 2.0,
+    );
+    let theme = &dd.theme;
+    draw_bordered_square_0(
+        theme.cell,
+        theme.cell_light,
+        theme.cell_dark,
+        theme.cell_light,
+        theme.cell_dark,
+        rect,
+        c.transform,
+        gl,
+    );
+    let text_color = theme.button_text;
+    for (i, line) in lines.iter().enumerate() {
+        graphics::text(
+            text_color,
+            font_size,
+            line,
+            &mut dd.glyph_cache,
+            c.transform.trans(
+                rect.x + padding,
+                rect.y + padding + line_height * (i as f64 + 0.75),
+            ),
+            gl,
+        )
+        .expect("Scoreboard text should be rendered");
+    }
+}
+
+pub enum MinimapBlock {
+    Closed,
+    Opened,
+    Flagged,
+}
+
+pub fn draw_minimap_block(
+    rect: Rect,
+    block: MinimapBlock,
+    theme: &Theme,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    let color = match block {
+        MinimapBlock::Closed => theme.cell,
+        MinimapBlock::Opened => theme.opened,
+        MinimapBlock::Flagged => theme.flag,
+    };
+    graphics::rectangle(color, rect.to_arr(), transform, gl);
+}
+
+pub fn draw_minimap_frame(
+    rect: Rect,
+    visible: Rect,
+    theme: &Theme,
+    transform: Matrix2d,
+    gl: &mut GlGraphics,
+) {
+    graphics::Rectangle::new_border(theme.cell_dark, 1.0).draw(
+        rect.to_arr(),
+        &graphics::DrawState::default(),
+        transform,
+        gl,
+    );
+    graphics::Rectangle::new_border(theme.menu_border, 1.0).draw(
+        visible.to_arr(),
+        &graphics::DrawState::default(),
+        transform,
+        gl,
+    );
+}
+
 pub fn draw_counter(rect: Rect, count: u32, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
+    let theme = &dd.theme;
     draw_bordered_square_0(
-        LIGHT_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
-        DARK_BLUE,
+        theme.display,
+        theme.cell_dark,
+        theme.cell_dark,
+        theme.cell_dark,
+        theme.cell_dark,
         rect,
         c.transform,
         gl,
     );
+    let text = format!("{:0>3.0}", count);
+    if draw_display_sprites_0(&text, rect, c.transform, gl, dd) {
+        return;
+    }
     graphics::text(
-        WHITE,
+        dd.theme.display_text,
         (rect.height * 0.8) as u32,
-        format!("{:0>3.0}", count).as_str(),
+        text.as_str(),
         &mut dd.glyph_cache,
-        c.transform.trans(rect.x + 20.0, rect.y + rect.height * 0.8),
+        c.transform
+            .trans(rect.x + rect.width * 0.2, rect.y + rect.height * 0.8),
         gl,
     )
     .expect("Cell text should be rendered");
 }
+
+/// A heart for each life in a row centered in the rect, lost ones dimmed. When the row
+/// doesn't fit, one heart with the lives left next to it, or on it in a small rect.
+pub fn draw_lives(
+    rect: Rect,
+    left: u32,
+    total: u32,
+    c: Context,
+    gl: &mut GlGraphics,
+    dd: &mut DrawData,
+) {
+    if total == 0 {
+        return;
+    }
+    let size = (rect.width / total as f64).min(rect.height);
+    if size < rect.height * 0.5 {
+        draw_lives_left_0(rect, left, c, gl, dd);
+        return;
+    }
+    let x = rect.x + (rect.width - size * total as f64) / 2.0;
+    let y = rect.y + (rect.height - size) / 2.0;
+    for life in 0..total {
+        let color = if life < left {
+            dd.theme.flag
+        } else {
+            dd.theme.cell_dark
+        };
+        draw_heart_0(
+            Rect::square(x + size * life as f64, y, size),
+            color,
+            gl,
+            c.transform,
+        );
+    }
+}
+
+fn draw_lives_left_0(rect: Rect, left: u32, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
+    let size = rect.width.min(rect.height);
+    let heart = Rect::square(rect.x, rect.y + (rect.height - size) / 2.0, size);
+    let color = if left > 0 {
+        dd.theme.flag
+    } else {
+        dd.theme.cell_dark
+    };
+    draw_heart_0(heart, color, gl, c.transform);
+    let (text, color, font_size, x, y) = if rect.width >= size * 2.5 {
+        let text = format!("x{}", left);
+        (
+            text,
+            dd.theme.button_text,
+            size * 0.8,
+            size * 1.1,
+            size * 0.8,
+        )
+    } else {
+        (
+            left.to_string(),
+            dd.theme.menu,
+            size * 0.5,
+            size * 0.35,
+            size * 0.65,
+        )
+    };
+    graphics::text(
+        color,
+        font_size as u32,
+        text.as_str(),
+        &mut dd.glyph_cache,
+        c.transform.trans(heart.x + x, heart.y + y),
+        gl,
+    )
+    .expect("Lives text should be rendered");
+}
+
+fn draw_heart_0(bounds: Rect, color: Color, gl: &mut GlGraphics, transform: Matrix2d) {
+    let s = bounds.width;
+    let transform = transform.trans(bounds.x, bounds.y);
+    graphics::ellipse(
+        color,
+        [0.1 * s, 0.15 * s, 0.42 * s, 0.42 * s],
+        transform,
+        gl,
+    );
+    graphics::ellipse(
+        color,
+        [0.48 * s, 0.15 * s, 0.42 * s, 0.42 * s],
+        transform,
+        gl,
+    );
+    graphics::polygon(
+        color,
+        &[
+            [0.12 * s, 0.45 * s],
+            [0.88 * s, 0.45 * s],
+            [0.5 * s, 0.88 * s],
+        ],
+        transform,
+        gl,
+    );
+}
+
+pub fn draw_menu_background(rect: Rect, c: Context, gl: &mut GlGraphics, dd: &DrawData) {
+    graphics::rectangle(dd.theme.menu, rect.to_arr(), c.transform, gl);
+    graphics::line_from_to(
+        dd.theme.menu_border,
+        2.0,
+        [rect.x, rect.y + rect.height - 1.0],
+        [rect.x + rect.width, rect.y + rect.height - 1.0],
+        c.transform,
+        gl,
+    );
+}
@@ -0,0 +1,68 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::game::Point;
+
+/// Everything which happens during a game. Cells are the ones which were clicked,
+/// `cells` counts the cells opened by a cascade or a chord.
+#[derive(Debug, Copy, Clone)]
+pub enum GameEvent {
+    GameStarted,
+    CellOpened { cell: Point<u32> },
+    CascadeOpened { cell: Point<u32>, cells: u32 },
+    Flagged { cell: Point<u32> },
+    Unflagged { cell: Point<u32> },
+    Chorded { cell: Point<u32>, cells: u32 },
+    Exploded { cell: Point<u32> },
+    Won { time: f64 },
+    Lost { time: f64 },
+    TimerTick { seconds: u32 },
+}
+
+pub trait GameObserver {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+/// Sends game events to every subscriber in the order they subscribed.
+#[derive(Default)]
+pub struct EventBus {
+    observers: Vec<Rc<RefCell<dyn GameObserver>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, observer: Rc<RefCell<dyn GameObserver>>) {
+        self.observers.push(observer);
+    }
+
+    pub fn emit(&self, event: GameEvent) {
+        for observer in &self.observers {
+            observer.borrow_mut().on_event(&event);
+        }
+    }
+}
+
+/// Writes every event to stderr as a line of text, handy when debugging bots and replays.
+pub struct EventLog;
+
+impl GameObserver for EventLog {
+    fn on_event(&mut self, event: &GameEvent) {
+        eprintln!("{}", describe(event));
+    }
+}
+
+fn describe(event: &GameEvent) -> String {
+    match event {
+        GameEvent::GameStarted => String::from("started"),
+        GameEvent::CellOpened { cell } => format!("opened {},{}", cell.x, cell.y),
+        GameEvent::CascadeOpened { cell, cells } => {
+            format!("cascade {},{} {}", cell.x, cell.y, cells)
+        }
+        GameEvent::Flagged { cell } => format!("flagged {},{}", cell.x, cell.y),
+        GameEvent::Unflagged { cell } => format!("unflagged {},{}", cell.x, cell.y),
+        GameEvent::Chorded { cell, cells } => format!("chorded {},{} {}", cell.x, cell.y, cells),
+        GameEvent::Exploded { cell } => format!("exploded {},{}", cell.x, cell.y),
+        GameEvent::Won { time } => format!("won {:.1}", time),
+        GameEvent::Lost { time } => format!("lost {:.1}", time),
+        GameEvent::TimerTick { seconds } => format!("tick {}", seconds),
+    }
+}
@@ -1,14 +1,21 @@
-use graphics::color::WHITE;
+use std::collections::VecDeque;
+
 use graphics::Context;
 use opengl_graphics::GlGraphics;
 use piston::{Button, ButtonArgs, ButtonState, MouseButton, RenderArgs, UpdateArgs};
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
+use crate::game::animation::{cascade_delay, chain_delay, Effect};
 use crate::game::cell::CellContent::Number;
-use crate::game::cell::{Cell, CellContent, CellState};
-use crate::game::draw::DrawData;
-use crate::game::{CellInteractionResult, FieldSize, GameElement, Point};
-use crate::CELL_SIZE;
+use crate::game::cell::{Cell, CellState, CellView};
+use crate::game::draw::{
+    draw_celebration, draw_explosion, draw_hover, draw_minimap_block, draw_minimap_frame, DrawData,
+    MinimapBlock, Rect,
+};
+use crate::game::geometry::Geometry;
+use crate::game::theme::Theme;
+use crate::game::{CellCommand, CellInteractionResult, FieldSize, GameElement, Point};
 
 pub const FIELD_SIZE_10: FieldSize = FieldSize {
     width: 8,
@@ -26,21 +33,121 @@ pub const FIELD_SIZE_99: FieldSize = FieldSize {
     mines: 99,
 };
 
+const MINIMAP_BLOCKS: f64 = 100.0;
+
+/// Board given cell by cell instead of by chance, like a puzzle.
+#[derive(Clone)]
+pub struct Layout {
+    pub mines: Vec<Point<u32>>,
+    // opened like a click would, so empty cells open their neighbours
+    pub opened: Vec<Point<u32>>,
+    pub flagged: Vec<Point<u32>>,
+}
+
+/// Cells whose view has changed since the changes were last taken from the field.
+#[derive(Default)]
+pub struct Changes {
+    cells: Vec<Point<u32>>,
+    // the whole board is new, like after a reset
+    all: bool,
+}
+
+impl Changes {
+    // a list longer than the board is no better than the whole board
+    fn mark(&mut self, point: Point<u32>, cell_count: u32) {
+        if self.all {
+            return;
+        }
+        if self.cells.len() >= cell_count as usize {
+            self.mark_all();
+        } else {
+            self.cells.push(point);
+        }
+    }
+
+    fn mark_all(&mut self) {
+        self.all = true;
+        self.cells.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.cells.is_empty()
+    }
+
+    /// Every changed cell once, `None` when the whole board has changed.
+    pub fn cells(&self) -> Option<&[Point<u32>]> {
+        if self.all {
+            None
+        } else {
+            Some(&self.cells)
+        }
+    }
+}
+
 pub struct Field {
     rows: Vec<Vec<Cell>>,
     size: FieldSize,
+    geometry: Geometry,
+    visible_area: Option<Rect>,
+    max_mines_per_cell: u8,
+    mine_cells: u32,
     flags: u32,
     open: u32,
+    // cells shown pressed while a button is held
+    pressed: Vec<Point<u32>>,
+    // left click on a number with enough flags around opens the neighbours
+    click_chord: bool,
+    // cell under the cursor, kept only when hover highlighting is on
+    hovered: Option<Point<u32>>,
+    hover: bool,
+    animations: bool,
+    effect: Option<Effect>,
+    // the same seed and the same first cell always give the same mines
+    seed: Option<u64>,
+    changes: Changes,
 }
 
 // public getters
 impl Field {
     pub fn height(&self) -> f64 {
-        self.size.height as f64 * CELL_SIZE
+        self.geometry.height(&self.size)
     }
 
     pub fn width(&self) -> f64 {
-        self.size.width as f64 * CELL_SIZE
+        self.geometry.width(&self.size)
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.geometry.cell_size()
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: f64) {
+        self.geometry.set_cell_size(cell_size);
+    }
+
+    /// Limits rendering to the cells intersecting the area, `None` renders everything.
+    pub fn set_visible_area(&mut self, area: Option<Rect>) {
+        self.visible_area = area;
+    }
+
+    pub fn size(&self) -> FieldSize {
+        self.size
+    }
+
+    pub fn contains(&self, point: Point<u32>) -> bool {
+        point.x < self.size.width && point.y < self.size.height
+    }
+
+    pub fn cell(&self, point: Point<u32>) -> &Cell {
+        self.cell_at_point(point)
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    pub fn cell_under(&self, point: Point<f64>) -> Option<Point<u32>> {
+        self.geometry.cell_at(point, &self.size)
     }
 
     pub fn mines(&self) -> u32 {
@@ -51,8 +158,35 @@ impl Field {
         self.flags
     }
 
+    /// Opened mines, only more than one when hitting a mine doesn't end the game.
+    pub fn hit_cells(&self) -> u32 {
+        self.hit_mines().count() as u32
+    }
+
+    /// Mines which are neither flagged nor hit, as the counter shows them.
+    pub fn mines_left(&self) -> u32 {
+        let hit: u32 = self.hit_mines().map(|cell| cell.mines() as u32).sum();
+        self.size.mines.saturating_sub(self.flags + hit)
+    }
+
+    fn hit_mines(&self) -> impl Iterator<Item = &Cell> {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|cell| cell.state() == CellState::Opened && cell.is_mine())
+    }
+
+    /// Share of the safe cells which are opened, from 0.0 to 1.0.
+    pub fn cleared(&self) -> f64 {
+        let safe = self.size.width * self.size.height - self.mine_cells;
+        if self.mine_cells == 0 || safe == 0 {
+            return 0.0;
+        }
+        1.0 - self.cells_left() as f64 / safe as f64
+    }
+
     pub fn cells_left(&self) -> u32 {
-        self.size.width * self.size.height - self.size.mines - self.open
+        self.size.width * self.size.height - self.mine_cells - self.open
     }
 }
 
@@ -71,60 +205,18 @@ impl Field {
     }
 
     fn mut_cell_at(&mut self, x: u32, y: u32) -> &mut Cell {
+        self.changes
+            .mark(Point { x, y }, self.size.width * self.size.height);
         &mut self.rows[y as usize][x as usize]
     }
 
+    // a pressed cell looks closed to the other players, so pressing isn't a change
+    fn cell_to_press(&mut self, point: Point<u32>) -> &mut Cell {
+        &mut self.rows[point.y as usize][point.x as usize]
+    }
+
     fn get_neighbours(&self, point: Point<u32>) -> Vec<Point<u32>> {
-        let mut neighbours = Vec::new();
-        if point.x > 0 {
-            neighbours.push(Point {
-                x: point.x - 1,
-                y: point.y,
-            });
-        }
-        if point.x < self.size.width - 1 {
-            neighbours.push(Point {
-                x: point.x + 1,
-                y: point.y,
-            });
-        }
-        if point.y > 0 {
-            neighbours.push(Point {
-                x: point.x,
-                y: point.y - 1,
-            });
-        }
-        if point.y < self.size.height - 1 {
-            neighbours.push(Point {
-                x: point.x,
-                y: point.y + 1,
-            });
-        }
-        if point.x > 0 && point.y > 0 {
-            neighbours.push(Point {
-                x: point.x - 1,
-                y: point.y - 1,
-            });
-        }
-        if point.x < self.size.width - 1 && point.y > 0 {
-            neighbours.push(Point {
-                x: point.x + 1,
-                y: point.y - 1,
-            });
-        }
-        if point.x > 0 && point.y < self.size.height - 1 {
-            neighbours.push(Point {
-                x: point.x - 1,
-                y: point.y + 1,
-            });
-        }
-        if point.x < self.size.width - 1 && point.y < self.size.height - 1 {
-            neighbours.push(Point {
-                x: point.x + 1,
-                y: point.y + 1,
-            });
-        }
-        neighbours
+        self.geometry.neighbours(point, &self.size)
     }
 }
 
@@ -142,39 +234,151 @@ fn generate_rows(size: &FieldSize) -> Vec<Vec<Cell>> {
 }
 
 impl Field {
-    pub fn new(size: FieldSize) -> Field {
-        let field = Field {
+    pub fn new(
+        size: FieldSize,
+        geometry: Geometry,
+        max_mines_per_cell: u8,
+        click_chord: bool,
+        hover: bool,
+        animations: bool,
+    ) -> Field {
+        Field {
             rows: generate_rows(&size),
             size,
+            geometry,
+            visible_area: None,
+            max_mines_per_cell,
+            mine_cells: 0,
             flags: 0,
             open: 0,
-        };
-        field
+            pressed: Vec::new(),
+            click_chord,
+            hovered: None,
+            hover,
+            animations,
+            effect: None,
+            seed: None,
+            changes: Changes::default(),
+        }
     }
 
     pub fn reset(&mut self) {
         self.rows = generate_rows(&self.size);
+        self.changes.mark_all();
+        self.mine_cells = 0;
         self.flags = 0;
         self.open = 0;
+        self.pressed.clear();
+        self.effect = None;
+    }
+
+    /// Every cell as the players see it, row by row.
+    pub fn view(&self) -> Vec<Vec<CellView>> {
+        self.rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.view()).collect())
+            .collect()
+    }
+
+    /// Replaces the board with one known only from what its players see, like the board
+    /// of a shared game which is owned by another player. Rows of a wrong size are ignored.
+    pub fn set_view(&mut self, view: &[Vec<CellView>]) {
+        if view.len() != self.size.height as usize
+            || view.iter().any(|row| row.len() != self.size.width as usize)
+        {
+            return;
+        }
+        self.pressed.clear();
+        self.changes.mark_all();
+        for (y, row) in view.iter().enumerate() {
+            for (x, &cell_view) in row.iter().enumerate() {
+                let position = Point {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                self.rows[y][x] = Cell::from_view(position, cell_view);
+            }
+        }
+        self.flags = self
+            .rows
+            .iter()
+            .flatten()
+            .map(|cell| cell.flags() as u32)
+            .sum();
+    }
+
+    /// Replaces one cell of a board known only from what its players see.
+    pub fn set_cell_view(&mut self, cell_point: Point<u32>, view: CellView) {
+        if !self.contains(cell_point) {
+            return;
+        }
+        let old_flags = self.cell_at_point(cell_point).flags() as u32;
+        *self.mut_cell_at_point(cell_point) = Cell::from_view(cell_point, view);
+        self.flags = self.flags - old_flags + self.cell_at_point(cell_point).flags() as u32;
+    }
+
+    /// Takes the cells changed since the last call, so they can be sent to other players.
+    pub fn take_changes(&mut self) -> Changes {
+        let mut changes = std::mem::take(&mut self.changes);
+        changes.cells.sort_by_key(|point| (point.y, point.x));
+        changes.cells.dedup();
+        changes
+    }
+
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
     }
 
     pub fn init(&mut self, except_pos: Point<u32>) {
         self.rows = generate_rows(&self.size);
+        self.changes.mark_all();
+        self.pressed.clear();
         self.randomize_mines(except_pos);
         let numbers = self.calculate_numbers();
         self.assign_numbers(numbers);
     }
 
+    pub fn lay_out(&mut self, layout: &Layout) {
+        self.reset();
+        for &mine in &layout.mines {
+            let cell = self.mut_cell_at_point(mine);
+            let new_mine_cell = !cell.is_mine();
+            cell.add_mine();
+            if new_mine_cell {
+                self.mine_cells += 1;
+            }
+        }
+        let numbers = self.calculate_numbers();
+        self.assign_numbers(numbers);
+        for &cell in &layout.opened {
+            self.open(cell);
+        }
+        for &cell in &layout.flagged {
+            self.toggle_flag(cell);
+        }
+    }
+
     fn randomize_mines(&mut self, except_pos: Point<u32>) {
-        let mut rng = rand::thread_rng();
+        // unlike StdRng, ChaCha8 stays the same algorithm on every platform and version,
+        // so a seed gives the same board to every player of a race or a daily challenge
+        let mut rng = match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
         let mut mines = self.size.mines;
+        let max_mines_per_cell = self.max_mines_per_cell;
+        self.mine_cells = 0;
         while mines > 0 {
             let y = rng.gen_range(0..self.size.height);
             let x = rng.gen_range(0..self.size.width);
             let cell = self.mut_cell_at(x, y);
-            if y != except_pos.y && x != except_pos.x && !cell.is_mine() {
-                cell.set_mine();
+            if y != except_pos.y && x != except_pos.x && cell.mines() < max_mines_per_cell {
+                let new_mine_cell = !cell.is_mine();
+                cell.add_mine();
                 mines -= 1;
+                if new_mine_cell {
+                    self.mine_cells += 1;
+                }
             }
         }
     }
@@ -188,9 +392,7 @@ impl Field {
                     let neighbours = self.get_neighbours(cell.position());
                     let mut mine_count = 0;
                     for neighbour in neighbours {
-                        if self.cell_at(neighbour.x, neighbour.y).is_mine() {
-                            mine_count += 1;
-                        }
+                        mine_count += self.cell_at(neighbour.x, neighbour.y).mines();
                     }
                     numbers[y as usize][x as usize] = mine_count;
                 }
@@ -210,18 +412,27 @@ impl Field {
         }
     }
 
+    // breadth first, so every wave of cells can be shown a bit later than the previous one
     fn open_neighbours(&mut self, point: Point<u32>) -> CellInteractionResult {
-        let neighbours = self.get_neighbours(point);
         let mut result = CellInteractionResult::Opened;
-        for neighbour in neighbours {
-            if self.cell_at(neighbour.x, neighbour.y).state() == CellState::Closed {
-                self.mut_cell_at(neighbour.x, neighbour.y).open();
-                self.open += 1;
-                if self.cell_at(neighbour.x, neighbour.y).content() == CellContent::Mine {
-                    result = CellInteractionResult::Exploded;
-                }
-                if self.cell_at(neighbour.x, neighbour.y).is_empty() {
-                    self.open_neighbours(neighbour);
+        let mut queue = VecDeque::from([(point, 0)]);
+        while let Some((point, wave)) = queue.pop_front() {
+            let delay = if self.animations {
+                cascade_delay(wave + 1)
+            } else {
+                0.0
+            };
+            for neighbour in self.get_neighbours(point) {
+                if self.cell_at(neighbour.x, neighbour.y).state() == CellState::Closed {
+                    self.mut_cell_at(neighbour.x, neighbour.y).open_after(delay);
+                    if self.cell_at(neighbour.x, neighbour.y).is_mine() {
+                        result = CellInteractionResult::Exploded;
+                    } else {
+                        self.open += 1;
+                    }
+                    if self.cell_at(neighbour.x, neighbour.y).is_empty() {
+                        queue.push_back((neighbour, wave + 1));
+                    }
                 }
             }
         }
@@ -232,9 +443,12 @@ impl Field {
         let neighbours = self.get_neighbours(point);
         let mut flags = 0;
         for neighbour in neighbours {
-            if self.cell_at(neighbour.x, neighbour.y).state() == CellState::Flagged {
-                flags += 1
-            }
+            let cell = self.cell_at(neighbour.x, neighbour.y);
+            // a hit mine is as known as a flagged one
+            flags += match cell.state() {
+                CellState::Opened => cell.mines(),
+                _ => cell.flags(),
+            };
         }
         flags
     }
@@ -242,68 +456,334 @@ impl Field {
 
 // events
 impl Field {
+    /// Opens the closed neighbours of a number which has as many flags around as it shows.
+    pub fn chord(&mut self, cell_point: Point<u32>) -> CellInteractionResult {
+        self.release_pressed();
+        if self.cell_at_point(cell_point).state() == CellState::Opened {
+            if let Number(number) = self.cell_at_point(cell_point).content() {
+                if number == self.flags_in_neighbours(cell_point) {
+                    return match self.open_neighbours(cell_point) {
+                        CellInteractionResult::Opened => CellInteractionResult::Chorded,
+                        result => result,
+                    };
+                }
+            }
+        }
+        CellInteractionResult::NoAction
+    }
+
+    /// Shows the cell and its closed neighbours pressed while a chord is held.
+    pub fn press_chord(&mut self, cell_point: Point<u32>) {
+        self.release_pressed();
+        let mut cells = self.get_neighbours(cell_point);
+        cells.push(cell_point);
+        for cell in cells {
+            if self.cell_at_point(cell).state() == CellState::Closed {
+                self.cell_to_press(cell).press();
+                self.pressed.push(cell);
+            }
+        }
+    }
+
+    /// Moves the press of a held button to the cell.
+    pub fn press_cell(&mut self, cell_point: Point<u32>) {
+        self.release_pressed();
+        if self.cell_at_point(cell_point).can_be_opened() {
+            self.cell_to_press(cell_point).press();
+            self.pressed.push(cell_point);
+        }
+    }
+
+    pub fn set_hovered(&mut self, cell_point: Option<Point<u32>>) {
+        if self.hover {
+            self.hovered = cell_point;
+        }
+    }
+
+    pub fn release_pressed(&mut self) {
+        for cell in std::mem::take(&mut self.pressed) {
+            self.cell_to_press(cell).unpress();
+        }
+    }
+
+    /// Shows presses and turns releases into moves, which are made by the game.
     pub fn button_action(
         &mut self,
         button_args: &ButtonArgs,
         cell_point: Point<u32>,
         both_buttons_flag: bool,
-    ) -> CellInteractionResult {
+    ) -> Option<CellCommand> {
         if both_buttons_flag {
-            if self.cell_at_point(cell_point).state() == CellState::Opened {
-                match self.cell_at_point(cell_point).content() {
-                    Number(number) => {
-                        if number == self.flags_in_neighbours(cell_point) {
-                            return self.open_neighbours(cell_point);
-                            return CellInteractionResult::Opened;
-                        }
-                    }
-                    _ => {}
+            self.release_pressed();
+            return Some(CellCommand::Chord);
+        } else if button_args.button == Button::from(MouseButton::Middle) {
+            return match button_args.state {
+                ButtonState::Press => {
+                    self.press_chord(cell_point);
+                    None
                 }
-            }
+                ButtonState::Release => {
+                    self.release_pressed();
+                    Some(CellCommand::Chord)
+                }
+            };
         } else if button_args.state == ButtonState::Press {
             if button_args.button == Button::from(MouseButton::Left) {
-                if self.cell_at_point(cell_point).can_be_opened() {
-                    self.mut_cell_at_point(cell_point).press();
-                }
+                self.press_cell(cell_point);
             }
         } else if button_args.state == ButtonState::Release {
             if button_args.button == Button::from(MouseButton::Left) {
-                if self.cell_at_point(cell_point).can_be_opened() {
-                    if self.cell_at_point(cell_point).is_empty() {
-                        self.open_neighbours(self.cell_at_point(cell_point).position());
-                    }
-                    self.mut_cell_at_point(cell_point).open();
-                    self.open += 1;
-                    return if self.cell_at_point(cell_point).is_mine() {
-                        CellInteractionResult::Exploded
-                    } else {
-                        CellInteractionResult::Opened
-                    };
+                self.release_pressed();
+                if self.click_chord && self.cell_at_point(cell_point).state() == CellState::Opened {
+                    return Some(CellCommand::Chord);
                 }
+                return Some(CellCommand::Open);
             } else if button_args.button == Button::from(MouseButton::Right) {
-                if self.cell_at_point(cell_point).state() == CellState::Closed {
-                    self.mut_cell_at_point(cell_point).flag();
-                    self.flags += 1;
-                    return CellInteractionResult::Flagged;
-                } else if self.cell_at_point(cell_point).state() == CellState::Flagged {
-                    self.mut_cell_at_point(cell_point).unflag();
-                    self.flags -= 1;
-                    return CellInteractionResult::Unflagged;
+                return Some(CellCommand::Flag);
+            }
+        }
+        None
+    }
+
+    pub fn command(
+        &mut self,
+        command: CellCommand,
+        cell_point: Point<u32>,
+    ) -> CellInteractionResult {
+        match command {
+            CellCommand::Open => self.open(cell_point),
+            CellCommand::Flag => self.toggle_flag(cell_point),
+            CellCommand::Chord => self.chord(cell_point),
+        }
+    }
+
+    pub fn open(&mut self, cell_point: Point<u32>) -> CellInteractionResult {
+        if !self.cell_at_point(cell_point).can_be_opened() {
+            return CellInteractionResult::NoAction;
+        }
+        // opened before the neighbours, so the cascade doesn't count it again
+        self.mut_cell_at_point(cell_point).open();
+        if self.cell_at_point(cell_point).is_mine() {
+            return CellInteractionResult::Exploded;
+        }
+        self.open += 1;
+        if self.cell_at_point(cell_point).is_empty() {
+            self.open_neighbours(cell_point);
+        }
+        CellInteractionResult::Opened
+    }
+
+    /// Every call adds a flag until the cell can't hold more mines, then clears it.
+    pub fn toggle_flag(&mut self, cell_point: Point<u32>) -> CellInteractionResult {
+        match self.cell_at_point(cell_point).state() {
+            CellState::Closed => {
+                self.mut_cell_at_point(cell_point).flag();
+                self.flags += 1;
+                CellInteractionResult::Flagged
+            }
+            CellState::Flagged(flags) if flags < self.max_mines_per_cell => {
+                self.mut_cell_at_point(cell_point).flag();
+                self.flags += 1;
+                CellInteractionResult::Flagged
+            }
+            CellState::Flagged(flags) => {
+                self.mut_cell_at_point(cell_point).unflag();
+                self.flags -= flags as u32;
+                CellInteractionResult::Unflagged
+            }
+            _ => CellInteractionResult::NoAction,
+        }
+    }
+}
+
+// animations
+impl Field {
+    /// Reveals the closed mines and the wrong flags one after another starting next to
+    /// the mine which was hit.
+    pub fn explode(&mut self) {
+        let hit: Vec<Point<f64>> = self
+            .hit_mines()
+            .map(|cell| self.cell_center_in_cells(cell.position()))
+            .collect();
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let cell = self.cell_at(x, y);
+                // a cell with more mines than flags still hides some
+                let hidden_mine = cell.is_mine()
+                    && cell.state() != CellState::Opened
+                    && cell.flags() < cell.mines();
+                let wrong_flag = cell.flags() > cell.mines();
+                if !hidden_mine && !wrong_flag {
+                    continue;
+                }
+                let center = self.cell_center_in_cells(Point { x, y });
+                let distance = hit
+                    .iter()
+                    .map(|hit| (hit.x - center.x).hypot(hit.y - center.y))
+                    .fold(f64::MAX, f64::min);
+                let delay = if self.animations {
+                    chain_delay(distance)
+                } else {
+                    0.0
+                };
+                if wrong_flag {
+                    self.mut_cell_at(x, y).reveal_wrong_flag_after(delay);
+                } else {
+                    self.mut_cell_at(x, y).reveal_after(delay);
                 }
             }
         }
-        CellInteractionResult::NoAction
+        if self.animations {
+            self.effect = self
+                .rows
+                .iter()
+                .flatten()
+                .find(|cell| cell.state() == CellState::Opened && cell.is_mine())
+                .map(|cell| Effect::explosion(cell.position()));
+        }
+    }
+
+    /// Flags every mine, which is all that is left after the other cells are opened.
+    pub fn flag_mines(&mut self) {
+        let mut flags = 0;
+        self.changes.mark_all();
+        // mines hit in a game with lives stay shown
+        for cell in self.rows.iter_mut().flatten() {
+            if cell.is_mine() && cell.state() != CellState::Opened {
+                cell.set_flags(cell.mines());
+                flags += cell.mines() as u32;
+            }
+        }
+        self.flags = flags;
+    }
+
+    pub fn celebrate(&mut self) {
+        if self.animations {
+            self.effect = Some(Effect::celebration());
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.effect.is_some() || self.rows.iter().flatten().any(|cell| cell.is_revealing())
+    }
+
+    /// Shows the final state of every running animation at once.
+    pub fn skip_animations(&mut self) {
+        self.effect = None;
+        for cell in self.rows.iter_mut().flatten() {
+            cell.skip_reveal();
+        }
+    }
+
+    // distances between cells don't depend on the zoom
+    fn cell_center_in_cells(&self, point: Point<u32>) -> Point<f64> {
+        let center = self.geometry.cell_center(point);
+        Point {
+            x: center.x / self.cell_size(),
+            y: center.y / self.cell_size(),
+        }
+    }
+
+    fn render_effect(&self, c: Context, gl: &mut GlGraphics, dd: &DrawData) {
+        match &self.effect {
+            Some(effect @ Effect::Explosion { center, .. }) => draw_explosion(
+                self.geometry.cell_center(*center),
+                self.cell_size() * 4.0,
+                effect.progress(),
+                &dd.theme,
+                c.transform,
+                gl,
+            ),
+            Some(effect @ Effect::Celebration { .. }) => draw_celebration(
+                Rect::new(0.0, 0.0, self.width(), self.height()),
+                effect.progress(),
+                &dd.theme,
+                c.transform,
+                gl,
+            ),
+            None => {}
+        }
+    }
+}
+
+// minimap
+impl Field {
+    /// Draws the whole field into the rect with one block per group of cells,
+    /// `visible` is the part of the field currently shown in field coordinates.
+    pub fn render_minimap(
+        &self,
+        rect: Rect,
+        visible: Rect,
+        theme: &Theme,
+        c: Context,
+        gl: &mut GlGraphics,
+    ) {
+        let cells_per_block = (self.size.width.max(self.size.height) as f64 / MINIMAP_BLOCKS)
+            .ceil()
+            .max(1.0) as u32;
+        let block_width = rect.width * cells_per_block as f64 / self.size.width as f64;
+        let block_height = rect.height * cells_per_block as f64 / self.size.height as f64;
+        for block_y in 0..self.size.height.div_ceil(cells_per_block) {
+            for block_x in 0..self.size.width.div_ceil(cells_per_block) {
+                let block = self.minimap_block(block_x, block_y, cells_per_block);
+                let block_rect = Rect::new(
+                    rect.x + block_x as f64 * block_width,
+                    rect.y + block_y as f64 * block_height,
+                    block_width.min(rect.x + rect.width - (rect.x + block_x as f64 * block_width)),
+                    block_height
+                        .min(rect.y + rect.height - (rect.y + block_y as f64 * block_height)),
+                );
+                draw_minimap_block(block_rect, block, theme, c.transform, gl);
+            }
+        }
+        let scale_x = rect.width / self.width();
+        let scale_y = rect.height / self.height();
+        let visible = Rect::new(
+            rect.x + visible.x.max(0.0) * scale_x,
+            rect.y + visible.y.max(0.0) * scale_y,
+            (visible.width * scale_x).min(rect.width),
+            (visible.height * scale_y).min(rect.height),
+        );
+        draw_minimap_frame(rect, visible, theme, c.transform, gl);
+    }
+
+    // explored blocks win over flagged ones to show progress
+    fn minimap_block(&self, block_x: u32, block_y: u32, cells_per_block: u32) -> MinimapBlock {
+        let mut block = MinimapBlock::Closed;
+        for y in block_y * cells_per_block..((block_y + 1) * cells_per_block).min(self.size.height)
+        {
+            for x in
+                block_x * cells_per_block..((block_x + 1) * cells_per_block).min(self.size.width)
+            {
+                match self.cell_at(x, y).state() {
+                    CellState::Opened => return MinimapBlock::Opened,
+                    CellState::Flagged(_) => block = MinimapBlock::Flagged,
+                    _ => {}
+                }
+            }
+        }
+        block
     }
 }
 
 impl GameElement for Field {
     fn render(&self, render_args: &RenderArgs, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
-        for row in 0..self.size.height {
-            for col in 0..self.size.width {
+        let (cols, rows) = match self.visible_area {
+            Some(area) => self.geometry.visible_cells(area, &self.size),
+            None => (0..self.size.width, 0..self.size.height),
+        };
+        for row in rows {
+            for col in cols.clone() {
                 let cell = &self.rows[row as usize][col as usize];
-                cell.render(render_args, c, gl, dd);
+                cell.render(&self.geometry, render_args, c, gl, dd);
             }
         }
+        if let Some(hovered) = self.hovered {
+            if let CellState::Closed | CellState::Flagged(_) = self.cell_at_point(hovered).state() {
+                draw_hover(&self.geometry, hovered, &dd.theme, c.transform, gl);
+            }
+        }
+        self.render_effect(c, gl, dd);
     }
     fn update(&mut self, update_args: &UpdateArgs) {
         for row in 0..self.size.height {
@@ -312,5 +792,76 @@ impl GameElement for Field {
                 cell.update(update_args);
             }
         }
+        if let Some(effect) = &mut self.effect {
+            if !effect.update(update_args.dt) {
+                self.effect = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::geometry::{GridKind, Kernel};
+
+    fn field() -> Field {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        Field::new(FIELD_SIZE_10, geometry, 1, false, false, false)
+    }
+
+    #[test]
+    fn changes_are_taken_once() {
+        let mut field = field();
+        field.take_changes();
+        let cell = Point { x: 2, y: 3 };
+        field.toggle_flag(cell);
+        field.toggle_flag(cell);
+        let changes = field.take_changes();
+        assert_eq!(changes.cells(), Some(&[cell][..]));
+        assert!(field.take_changes().is_empty());
+    }
+
+    #[test]
+    fn presses_are_not_changes() {
+        let mut field = field();
+        field.take_changes();
+        field.press_chord(Point { x: 2, y: 3 });
+        field.release_pressed();
+        assert!(field.take_changes().is_empty());
+    }
+
+    #[test]
+    fn reset_changes_every_cell() {
+        let mut field = field();
+        field.toggle_flag(Point { x: 2, y: 3 });
+        field.reset();
+        assert!(field.take_changes().cells().is_none());
+    }
+
+    #[test]
+    fn flags_are_checked_against_every_mine_of_a_cell() {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        let mut field = Field::new(FIELD_SIZE_10, geometry, 3, false, false, false);
+        let (over, under, exact) = (
+            Point { x: 0, y: 0 },
+            Point { x: 4, y: 0 },
+            Point { x: 7, y: 7 },
+        );
+        field.lay_out(&Layout {
+            mines: vec![over, over, under, under, exact, exact],
+            opened: Vec::new(),
+            flagged: Vec::new(),
+        });
+        for _ in 0..3 {
+            field.toggle_flag(over);
+        }
+        field.toggle_flag(under);
+        field.toggle_flag(exact);
+        field.toggle_flag(exact);
+        field.explode();
+        assert_eq!(field.cell(over).view(), CellView::WrongFlag);
+        assert_eq!(field.cell(under).view(), CellView::Mine(2));
+        assert_eq!(field.cell(exact).view(), CellView::Flagged(2));
     }
 }
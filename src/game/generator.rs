@@ -0,0 +1,678 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::game::field::Layout;
+use crate::game::geometry::Geometry;
+use crate::game::settings::Settings;
+use crate::game::{Deal, FieldSize, Game, GameState, Point};
+
+// boards tried for a puzzle which needs the hardest allowed technique
+const ATTEMPTS: u32 = 30;
+
+/// Deductions of the solver from the easiest, the hardest one needed grades a puzzle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Technique {
+    /// A number has no mines left around it, or as many as closed cells.
+    Single,
+    /// The closed cells around a number are a part of the ones around another.
+    Subset,
+    /// The mines left on the whole board are taken into account.
+    Count,
+}
+
+impl Technique {
+    pub fn parse(difficulty: &str) -> Option<Technique> {
+        match difficulty {
+            "easy" => Some(Technique::Single),
+            "medium" => Some(Technique::Subset),
+            "hard" => Some(Technique::Count),
+            _ => None,
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            Technique::Single => "every number can be read on its own",
+            Technique::Subset => "compare the numbers next to each other",
+            Technique::Count => "count the mines left",
+        }
+    }
+
+    pub fn difficulty(self) -> &'static str {
+        match self {
+            Technique::Single => "easy",
+            Technique::Subset => "medium",
+            Technique::Count => "hard",
+        }
+    }
+}
+
+/// Mines and numbers of a board, everything the solver checks its deductions against.
+struct Solution {
+    size: FieldSize,
+    mines: Vec<bool>,
+    numbers: Vec<u8>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl Solution {
+    fn new(size: FieldSize, geometry: &Geometry, mines: Vec<bool>) -> Solution {
+        let neighbours: Vec<Vec<usize>> = (0..mines.len())
+            .map(|index| {
+                geometry
+                    .neighbours(point(index, &size), &size)
+                    .into_iter()
+                    .map(|neighbour| (neighbour.y * size.width + neighbour.x) as usize)
+                    .collect()
+            })
+            .collect();
+        let numbers = neighbours
+            .iter()
+            .map(|around| around.iter().filter(|&&cell| mines[cell]).count() as u8)
+            .collect();
+        Solution {
+            size,
+            mines,
+            numbers,
+            neighbours,
+        }
+    }
+}
+
+fn point(index: usize, size: &FieldSize) -> Point<u32> {
+    Point {
+        x: index as u32 % size.width,
+        y: index as u32 / size.width,
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Known {
+    Unknown,
+    Safe,
+    Mine,
+}
+
+// closed cells of which the given number are mines, sorted
+struct Constraint {
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+/// Solves the board from the revealed cells with the allowed techniques, like a player
+/// who never guesses. Returns the hardest technique needed, or `None` when the board
+/// can't be finished: then there is more than one solution or it needs harder logic.
+fn solve(solution: &Solution, revealed: &[bool], allowed: Technique) -> Option<Technique> {
+    let mut known = start(solution, revealed);
+    deduce(solution, &mut known, allowed, |_| false)
+}
+
+fn start(solution: &Solution, revealed: &[bool]) -> Vec<Known> {
+    let mut known = vec![Known::Unknown; revealed.len()];
+    for cell in (0..revealed.len()).filter(|&cell| revealed[cell]) {
+        reveal(solution, &mut known, cell);
+    }
+    known
+}
+
+/// Whether a solvable board stays solvable with one more revealed cell hidden. Knowing
+/// more never takes a deduction away, so once the cell is deduced again the rest follows
+/// like before, and the solver can stop there instead of finishing the board.
+fn can_hide(solution: &Solution, revealed: &[bool], cell: usize, allowed: Technique) -> bool {
+    let mut known = start(solution, revealed);
+    deduce(solution, &mut known, allowed, |known| {
+        known[cell] == Known::Safe
+    })
+    .is_some()
+}
+
+// deduces until every safe cell is known or `done` says the known cells are enough
+fn deduce(
+    solution: &Solution,
+    known: &mut [Known],
+    allowed: Technique,
+    done: impl Fn(&[Known]) -> bool,
+) -> Option<Technique> {
+    let mut hardest = Technique::Single;
+    loop {
+        let safe_left =
+            (0..known.len()).any(|cell| known[cell] == Known::Unknown && !solution.mines[cell]);
+        if !safe_left || done(known) {
+            return Some(hardest);
+        }
+        let constraints = constraints(solution, known);
+        let mut technique = Technique::Single;
+        let mut deduced = deduce_singles(&constraints);
+        if deduced.is_empty() && allowed >= Technique::Subset {
+            technique = Technique::Subset;
+            deduced = deduce_subsets(&constraints, &constraints);
+        }
+        if deduced.is_empty() && allowed >= Technique::Count {
+            technique = Technique::Count;
+            let unknown: Vec<usize> = (0..known.len())
+                .filter(|&cell| known[cell] == Known::Unknown)
+                .collect();
+            let found = known.iter().filter(|&&cell| cell == Known::Mine).count();
+            let total = [Constraint {
+                mines: solution.size.mines as usize - found,
+                cells: unknown,
+            }];
+            deduced = deduce_singles(&total);
+            if deduced.is_empty() {
+                deduced = deduce_subsets(&constraints, &total);
+            }
+        }
+        if deduced.is_empty() {
+            return None;
+        }
+        hardest = hardest.max(technique);
+        for (cell, mine) in deduced {
+            if mine {
+                known[cell] = Known::Mine;
+            } else {
+                reveal(solution, known, cell);
+            }
+        }
+    }
+}
+
+// empty cells open their neighbours like in the game
+fn reveal(solution: &Solution, known: &mut [Known], cell: usize) {
+    let mut cells = vec![cell];
+    while let Some(cell) = cells.pop() {
+        if known[cell] == Known::Safe {
+            continue;
+        }
+        known[cell] = Known::Safe;
+        if solution.numbers[cell] == 0 {
+            cells.extend(&solution.neighbours[cell]);
+        }
+    }
+}
+
+fn constraints(solution: &Solution, known: &[Known]) -> Vec<Constraint> {
+    let mut constraints: Vec<Constraint> = (0..known.len())
+        .filter(|&cell| known[cell] == Known::Safe)
+        .filter_map(|cell| {
+            let around = &solution.neighbours[cell];
+            let cells: Vec<usize> = around
+                .iter()
+                .copied()
+                .filter(|&neighbour| known[neighbour] == Known::Unknown)
+                .collect();
+            let found = around
+                .iter()
+                .filter(|&&neighbour| known[neighbour] == Known::Mine)
+                .count();
+            (!cells.is_empty()).then(|| Constraint {
+                cells,
+                mines: solution.numbers[cell] as usize - found,
+            })
+        })
+        .collect();
+    for constraint in &mut constraints {
+        constraint.cells.sort_unstable();
+    }
+    constraints
+}
+
+// cells with their deduced content, `true` for a mine
+fn deduce_singles(constraints: &[Constraint]) -> Vec<(usize, bool)> {
+    let mut deduced = Vec::new();
+    for constraint in constraints {
+        if constraint.mines == 0 || constraint.mines == constraint.cells.len() {
+            let mine = constraint.mines > 0;
+            deduced.extend(constraint.cells.iter().map(|&cell| (cell, mine)));
+        }
+    }
+    deduced
+}
+
+// the cells of a constraint which aren't in a smaller one have the difference of mines
+fn deduce_subsets(smaller: &[Constraint], larger: &[Constraint]) -> Vec<(usize, bool)> {
+    // a larger constraint has the first cell of the smaller one, so only the larger ones
+    // with that cell are compared instead of every pair
+    let mut with_cell: HashMap<usize, Vec<&Constraint>> = HashMap::new();
+    for large in larger {
+        for &cell in &large.cells {
+            with_cell.entry(cell).or_default().push(large);
+        }
+    }
+    let mut deduced = Vec::new();
+    for small in smaller {
+        let Some(larger) = small.cells.first().and_then(|cell| with_cell.get(cell)) else {
+            continue;
+        };
+        for large in larger {
+            if small.cells.len() >= large.cells.len()
+                || !small
+                    .cells
+                    .iter()
+                    .all(|cell| large.cells.binary_search(cell).is_ok())
+            {
+                continue;
+            }
+            let rest: Vec<usize> = large
+                .cells
+                .iter()
+                .copied()
+                .filter(|cell| small.cells.binary_search(cell).is_err())
+                .collect();
+            let mines = large.mines - small.mines;
+            if mines == 0 || mines == rest.len() {
+                deduced.extend(rest.iter().map(|&cell| (cell, mines > 0)));
+            }
+        }
+    }
+    deduced
+}
+
+/// Tells the cells a player opens by logic from the guesses. The solver knows only the
+/// techniques of training, so a deduction harder than counting the mines left is taken for
+/// a guess too.
+pub struct Guesses {
+    solution: Solution,
+    // cells known from the start of the puzzle, the opened and the flagged ones
+    start: Vec<Known>,
+    known: Vec<Known>,
+}
+
+impl Guesses {
+    pub fn new(size: FieldSize, geometry: &Geometry, layout: &Layout) -> Guesses {
+        let index = |cell: &Point<u32>| (cell.y * size.width + cell.x) as usize;
+        let mut mines = vec![false; (size.width * size.height) as usize];
+        for cell in &layout.mines {
+            mines[index(cell)] = true;
+        }
+        let solution = Solution::new(size, geometry, mines);
+        let mut revealed = vec![false; solution.mines.len()];
+        for cell in &layout.opened {
+            revealed[index(cell)] = true;
+        }
+        let mut start = start(&solution, &revealed);
+        for cell in &layout.flagged {
+            start[index(cell)] = Known::Mine;
+        }
+        Guesses {
+            solution,
+            known: start.clone(),
+            start,
+        }
+    }
+
+    /// Forgets the moves, like a new game of the puzzle.
+    pub fn reset(&mut self) {
+        self.known.clone_from(&self.start);
+    }
+
+    /// Takes the cells opened since the last call and returns one which couldn't be
+    /// told from the cells opened before, opening a mine is always a guess.
+    pub fn open(&mut self, cells: &[Point<u32>]) -> Option<Point<u32>> {
+        let size = self.solution.size;
+        let opened: Vec<usize> = cells
+            .iter()
+            .map(|cell| (cell.y * size.width + cell.x) as usize)
+            .filter(|&cell| self.known[cell] != Known::Safe)
+            .collect();
+        if opened.is_empty() {
+            return None;
+        }
+        let mut deduced = self.known.clone();
+        deduce(&self.solution, &mut deduced, Technique::Count, |known| {
+            opened.iter().all(|&cell| known[cell] != Known::Unknown)
+        });
+        let guess = opened
+            .iter()
+            .copied()
+            .find(|&cell| self.solution.mines[cell] || deduced[cell] != Known::Safe);
+        for &cell in &opened {
+            if self.solution.mines[cell] {
+                // a hit mine is as known as a flagged one
+                self.known[cell] = Known::Mine;
+            } else {
+                reveal(&self.solution, &mut self.known, cell);
+            }
+        }
+        guess.map(|cell| point(cell, &size))
+    }
+}
+
+/// Makes a puzzle out of a random board: every safe cell starts revealed, then cells are
+/// hidden one by one as long as the solver can still finish the board with the allowed
+/// techniques. Several boards are tried to find one which needs the hardest of them.
+/// Returns `None` when no board leaves anything to solve, like on tiny boards.
+pub fn generate(
+    size: FieldSize,
+    geometry: &Geometry,
+    allowed: Technique,
+    rng: &mut ChaCha8Rng,
+) -> Option<(Layout, Technique)> {
+    let mut best: Option<(Layout, Technique)> = None;
+    for _ in 0..ATTEMPTS {
+        let Some((layout, technique)) = attempt(size, geometry, allowed, rng) else {
+            continue;
+        };
+        if technique == allowed {
+            return Some((layout, technique));
+        }
+        if best.as_ref().is_none_or(|(_, best)| technique > *best) {
+            best = Some((layout, technique));
+        }
+    }
+    best
+}
+
+fn attempt(
+    size: FieldSize,
+    geometry: &Geometry,
+    allowed: Technique,
+    rng: &mut ChaCha8Rng,
+) -> Option<(Layout, Technique)> {
+    let cells = (size.width * size.height) as usize;
+    let mut mines = vec![false; cells];
+    let mut order: Vec<usize> = (0..cells).collect();
+    order.shuffle(rng);
+    for &cell in order.iter().take(size.mines as usize) {
+        mines[cell] = true;
+    }
+    let solution = Solution::new(size, geometry, mines);
+    let mut revealed: Vec<bool> = solution.mines.iter().map(|mine| !mine).collect();
+    order.shuffle(rng);
+    for &cell in order.iter().filter(|&&cell| !solution.mines[cell]) {
+        revealed[cell] = false;
+        if !can_hide(&solution, &revealed, cell, allowed) {
+            revealed[cell] = true;
+        }
+    }
+    let technique = solve(&solution, &revealed, allowed)?;
+    // the puzzle has to leave something to solve
+    let mut known = vec![Known::Unknown; cells];
+    for cell in (0..cells).filter(|&cell| revealed[cell]) {
+        reveal(&solution, &mut known, cell);
+    }
+    if !(0..cells).any(|cell| known[cell] == Known::Unknown && !solution.mines[cell]) {
+        return None;
+    }
+    let points = |cells: Vec<usize>| cells.into_iter().map(|cell| point(cell, &size)).collect();
+    let layout = Layout {
+        mines: points((0..cells).filter(|&cell| solution.mines[cell]).collect()),
+        opened: points((0..cells).filter(|&cell| revealed[cell]).collect()),
+        flagged: Vec::new(),
+    };
+    Some((layout, technique))
+}
+
+/// Endless puzzles on the board of the settings, New Game makes a fresh one.
+pub struct Training {
+    // puzzles are made on their own thread one ahead of the one being played, so making
+    // them doesn't freeze the window
+    next: Receiver<Option<(Layout, Technique)>>,
+    // grade of the puzzle being played
+    technique: Technique,
+    puzzles: u32,
+    solved: u32,
+    state: GameState,
+    // a new game has been asked for before its puzzle was made
+    waiting: bool,
+}
+
+impl Training {
+    /// Puzzles need no technique harder than the allowed one. The seed of the settings
+    /// gives the same puzzles every time.
+    pub fn new(allowed: Technique, settings: &Settings) -> Training {
+        let size = settings.field_size;
+        let geometry = Geometry::new(settings.grid, settings.kernel.clone());
+        let mut rng = match settings.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        let (sender, next) = mpsc::sync_channel(0);
+        thread::spawn(move || loop {
+            let puzzle = generate(size, &geometry, allowed, &mut rng);
+            // nobody takes puzzles once the game is closed
+            if sender.send(puzzle).is_err() {
+                return;
+            }
+        });
+        Training {
+            next,
+            technique: allowed,
+            puzzles: 0,
+            solved: 0,
+            state: GameState::Ready,
+            waiting: false,
+        }
+    }
+
+    /// Deals the first puzzle, waiting until it is made.
+    pub fn deal(&mut self, game: &mut Game) -> Result<(), String> {
+        let puzzle = self
+            .next
+            .recv()
+            .map_err(|_| String::from("Puzzles can't be made"))?;
+        self.deal_puzzle(puzzle, game)
+    }
+
+    fn deal_puzzle(
+        &mut self,
+        puzzle: Option<(Layout, Technique)>,
+        game: &mut Game,
+    ) -> Result<(), String> {
+        let (layout, technique) = puzzle.ok_or("The board is too small for puzzles")?;
+        self.technique = technique;
+        self.puzzles += 1;
+        self.waiting = false;
+        game.set_race(false);
+        game.set_deal(Some(Deal::Layout(layout)));
+        game.set_scoreboard(self.scoreboard());
+        Ok(())
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        if self.waiting {
+            self.take_next(game);
+        }
+        let state = game.state();
+        if state == self.state {
+            return;
+        }
+        self.state = state;
+        match state {
+            GameState::Win => {
+                self.solved += 1;
+                game.set_scoreboard(self.scoreboard());
+            }
+            GameState::Ready => {
+                // until the puzzle is made the board stays empty and can't be played,
+                // like in a race which hasn't been started
+                self.waiting = true;
+                game.set_race(true);
+                game.set_deal(None);
+                game.set_scoreboard(vec![String::from("Making the next puzzle...")]);
+                self.take_next(game);
+            }
+            _ => {}
+        }
+    }
+
+    fn take_next(&mut self, game: &mut Game) {
+        let dealt = match self.next.try_recv() {
+            Ok(puzzle) => self.deal_puzzle(puzzle, game),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(String::from("Puzzles can't be made")),
+        };
+        if let Err(e) = dealt {
+            eprintln!("{}", e);
+            self.waiting = false;
+            game.set_race(false);
+        }
+    }
+
+    fn scoreboard(&self) -> Vec<String> {
+        vec![
+            format!("Training puzzle {}", self.puzzles),
+            format!("{}: {}", self.technique.difficulty(), self.technique.hint()),
+            format!("Solved {} of {}", self.solved, self.puzzles),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::field::{FIELD_SIZE_10, FIELD_SIZE_40};
+    use crate::game::geometry::{GridKind, Kernel};
+    use crate::game::CellCommand;
+
+    // `*` is a mine, `o` a revealed cell and `.` a hidden one
+    fn board(rows: &[&str]) -> (Solution, Vec<bool>) {
+        let cells: Vec<char> = rows.iter().flat_map(|row| row.chars()).collect();
+        let mines: Vec<bool> = cells.iter().map(|&cell| cell == '*').collect();
+        let size = FieldSize {
+            width: rows[0].len() as u32,
+            height: rows.len() as u32,
+            mines: mines.iter().filter(|&&mine| mine).count() as u32,
+        };
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        let revealed = cells.iter().map(|&cell| cell == 'o').collect();
+        (Solution::new(size, &geometry, mines), revealed)
+    }
+
+    #[test]
+    fn singles_are_easy() {
+        // the first 1 finds the mine, then the second 1 has it already
+        let (solution, revealed) = board(&["o*o."]);
+        assert_eq!(
+            solve(&solution, &revealed, Technique::Single),
+            Some(Technique::Single)
+        );
+    }
+
+    #[test]
+    fn subsets_are_medium() {
+        // every number is 1, the middle one is only read against the ones beside it
+        let (solution, revealed) = board(&[".*.", "ooo"]);
+        assert_eq!(solve(&solution, &revealed, Technique::Single), None);
+        assert_eq!(
+            solve(&solution, &revealed, Technique::Subset),
+            Some(Technique::Subset)
+        );
+    }
+
+    #[test]
+    fn count_is_hard() {
+        // the middle cell touches no number, only both mines being found tells it's safe
+        let (solution, revealed) = board(&["o*.*o"]);
+        assert_eq!(solve(&solution, &revealed, Technique::Subset), None);
+        assert_eq!(
+            solve(&solution, &revealed, Technique::Count),
+            Some(Technique::Count)
+        );
+    }
+
+    #[test]
+    fn fifty_fifty_is_unsolvable() {
+        let (solution, revealed) = board(&["*.", "oo"]);
+        assert_eq!(solve(&solution, &revealed, Technique::Count), None);
+    }
+
+    #[test]
+    fn generated_puzzles_are_solvable() {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for allowed in [Technique::Single, Technique::Subset, Technique::Count] {
+            let (layout, technique) = generate(FIELD_SIZE_40, &geometry, allowed, &mut rng)
+                .expect("Intermediate boards should have puzzles");
+            assert!(technique <= allowed);
+            assert_eq!(layout.mines.len() as u32, FIELD_SIZE_40.mines);
+            let index = |cell: &Point<u32>| (cell.y * FIELD_SIZE_40.width + cell.x) as usize;
+            let cells = (FIELD_SIZE_40.width * FIELD_SIZE_40.height) as usize;
+            let mut mines = vec![false; cells];
+            for cell in &layout.mines {
+                mines[index(cell)] = true;
+            }
+            let mut revealed = vec![false; cells];
+            for cell in &layout.opened {
+                assert!(!mines[index(cell)]);
+                revealed[index(cell)] = true;
+            }
+            // solving means every safe cell is reached without a guess
+            let solution = Solution::new(FIELD_SIZE_40, &geometry, mines);
+            assert_eq!(solve(&solution, &revealed, allowed), Some(technique));
+        }
+    }
+
+    #[test]
+    fn hidden_cells_are_checked_like_by_a_full_solve() {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        for allowed in [Technique::Single, Technique::Subset, Technique::Count] {
+            let (layout, _) = generate(FIELD_SIZE_40, &geometry, allowed, &mut rng).unwrap();
+            let index = |cell: &Point<u32>| (cell.y * FIELD_SIZE_40.width + cell.x) as usize;
+            let cells = (FIELD_SIZE_40.width * FIELD_SIZE_40.height) as usize;
+            let mut mines = vec![false; cells];
+            for cell in &layout.mines {
+                mines[index(cell)] = true;
+            }
+            let solution = Solution::new(FIELD_SIZE_40, &geometry, mines);
+            let mut revealed = vec![false; cells];
+            for cell in &layout.opened {
+                revealed[index(cell)] = true;
+            }
+            for cell in layout.opened.iter().map(index) {
+                revealed[cell] = false;
+                assert_eq!(
+                    can_hide(&solution, &revealed, cell, allowed),
+                    solve(&solution, &revealed, allowed).is_some()
+                );
+                revealed[cell] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn next_puzzle_is_dealt_after_new_game() {
+        let settings = || Settings {
+            field_size: FIELD_SIZE_10,
+            seed: Some(1),
+            animations: false,
+            ..Settings::default()
+        };
+        let mut game = Game::new(settings());
+        let mut training = Training::new(Technique::Single, &settings());
+        training.deal(&mut game).unwrap();
+        game.command(CellCommand::Flag, Point { x: 0, y: 0 });
+        training.update(&mut game);
+        assert_eq!(game.state(), GameState::Playing);
+        game.new_game();
+        // the puzzle may still be in the making, then the board waits for it
+        training.update(&mut game);
+        for _ in 0..500 {
+            if !training.waiting {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(10));
+            training.update(&mut game);
+        }
+        assert!(!training.waiting);
+        assert_eq!(training.puzzles, 2);
+        assert_eq!(game.state(), GameState::Ready);
+    }
+
+    #[test]
+    fn tiny_boards_have_no_puzzles() {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        let size = FieldSize {
+            width: 2,
+            height: 2,
+            mines: 1,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert!(generate(size, &geometry, Technique::Count, &mut rng).is_none());
+    }
+}
@@ -0,0 +1,679 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::draw::Rect;
+use crate::game::settings::MAX_MINES_PER_CELL;
+use crate::game::DEFAULT_CELL_SIZE;
+use crate::game::{FieldSize, Point};
+
+const SQUARE_NEIGHBOURS: [(i64, i64); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+// custom kernels are limited to keep their numbers readable and the neighbours cheap
+const MAX_KERNEL_OFFSETS: usize = 24;
+const MAX_KERNEL_DISTANCE: i64 = 3;
+// numbers of the cells are kept in a byte, even around a cell of the widest kernel,
+// which has more neighbours than a triangle
+const _: () = assert!(
+    MAX_KERNEL_OFFSETS >= TRIANGLE_NEIGHBOURS_UP.len()
+        && MAX_KERNEL_OFFSETS * MAX_MINES_PER_CELL as usize <= u8::MAX as usize
+);
+const ORTHOGONAL_NEIGHBOURS: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const KNIGHT_NEIGHBOURS: [(i64, i64); 8] = [
+    (-2, -1),
+    (-1, -2),
+    (1, -2),
+    (2, -1),
+    (2, 1),
+    (1, 2),
+    (-1, 2),
+    (-2, 1),
+];
+// "odd-r" offset layout: odd rows are shifted half a cell to the right
+const HEX_NEIGHBOURS_EVEN_ROW: [(i64, i64); 6] =
+    [(-1, 0), (1, 0), (-1, -1), (0, -1), (-1, 1), (0, 1)];
+const HEX_NEIGHBOURS_ODD_ROW: [(i64, i64); 6] = [(-1, 0), (1, 0), (0, -1), (1, -1), (0, 1), (1, 1)];
+// triangles sharing an edge or a vertex with an upward pointing triangle
+const TRIANGLE_NEIGHBOURS_UP: [(i64, i64); 12] = [
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-2, 1),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (2, 1),
+];
+// same as above mirrored vertically
+const TRIANGLE_NEIGHBOURS_DOWN: [(i64, i64); 12] = [
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (-2, -1),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (2, -1),
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GridKind {
+    #[serde(rename = "square")]
+    Square,
+    #[serde(rename = "hex")]
+    Hexagonal,
+    #[serde(rename = "tri")]
+    Triangular,
+}
+
+impl GridKind {
+    /// Name of the grid as in the `--grid` option.
+    pub fn name(self) -> &'static str {
+        match self {
+            GridKind::Square => "square",
+            GridKind::Hexagonal => "hex",
+            GridKind::Triangular => "tri",
+        }
+    }
+}
+
+/// Cell offsets which count as neighbours on a square grid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<(i64, i64)>", into = "Vec<(i64, i64)>")]
+pub struct Kernel {
+    offsets: Vec<(i64, i64)>,
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Kernel::from_offsets(&SQUARE_NEIGHBOURS)
+    }
+}
+
+impl Kernel {
+    fn from_offsets(offsets: &[(i64, i64)]) -> Kernel {
+        Kernel {
+            offsets: offsets.to_vec(),
+        }
+    }
+
+    /// Every cell within the given distance, like the default kernel but wider.
+    fn radius(radius: i64) -> Kernel {
+        let mut offsets = Vec::new();
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx != 0 || dy != 0 {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        Kernel { offsets }
+    }
+
+    /// Accepts one of `moore`, `orthogonal`, `knight`, `extended`
+    /// or a custom list of offsets like `0,-1;0,1;-2,0;2,0`.
+    pub fn parse(spec: &str) -> Result<Kernel, String> {
+        match spec {
+            "moore" => return Ok(Kernel::default()),
+            "orthogonal" => return Ok(Kernel::from_offsets(&ORTHOGONAL_NEIGHBOURS)),
+            "knight" => return Ok(Kernel::from_offsets(&KNIGHT_NEIGHBOURS)),
+            "extended" => return Ok(Kernel::radius(2)),
+            _ => {}
+        }
+        let mut offsets = Vec::new();
+        for offset in spec.split(';') {
+            let parsed = offset
+                .split_once(',')
+                .and_then(|(dx, dy)| Some((dx.trim().parse().ok()?, dy.trim().parse().ok()?)));
+            match parsed {
+                Some(offset) if !offsets.contains(&offset) => offsets.push(offset),
+                Some(_) => {}
+                None => return Err(format!("Unknown kernel {}", spec)),
+            }
+        }
+        Kernel::try_from(offsets)
+    }
+
+    /// The kernel as `parse` reads it, by name when it has one.
+    pub fn spec(&self) -> String {
+        for name in ["moore", "orthogonal", "knight", "extended"] {
+            if Kernel::parse(name).as_ref() == Ok(self) {
+                return String::from(name);
+            }
+        }
+        let offsets: Vec<String> = self
+            .offsets
+            .iter()
+            .map(|(dx, dy)| format!("{},{}", dx, dy))
+            .collect();
+        offsets.join(";")
+    }
+}
+
+// kernels of other players are checked like the ones on the command line
+impl TryFrom<Vec<(i64, i64)>> for Kernel {
+    type Error = String;
+
+    fn try_from(offsets: Vec<(i64, i64)>) -> Result<Kernel, String> {
+        if offsets.contains(&(0, 0)) {
+            return Err(String::from("Kernel can't contain the cell itself"));
+        }
+        if offsets.len() > MAX_KERNEL_OFFSETS {
+            return Err(format!(
+                "Kernel can have at most {} offsets",
+                MAX_KERNEL_OFFSETS
+            ));
+        }
+        if offsets
+            .iter()
+            .any(|&(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()) > MAX_KERNEL_DISTANCE as u64)
+        {
+            return Err(format!(
+                "Kernel offsets can be at most {} cells away",
+                MAX_KERNEL_DISTANCE
+            ));
+        }
+        Ok(Kernel { offsets })
+    }
+}
+
+impl From<Kernel> for Vec<(i64, i64)> {
+    fn from(kernel: Kernel) -> Self {
+        kernel.offsets
+    }
+}
+
+/// Maps cell coordinates to screen space and back for a given tiling.
+/// All screen coordinates are relative to the top left corner of the field.
+#[derive(Debug, Clone)]
+pub struct Geometry {
+    kind: GridKind,
+    kernel: Kernel,
+    cell_size: f64,
+}
+
+impl Geometry {
+    /// Kernel only applies to square grids, other tilings have their natural neighbourhood.
+    pub fn new(kind: GridKind, kernel: Kernel) -> Geometry {
+        Geometry {
+            kind,
+            kernel,
+            cell_size: DEFAULT_CELL_SIZE,
+        }
+    }
+
+    pub fn kind(&self) -> GridKind {
+        self.kind
+    }
+
+    /// Neighbour offsets of the square grid, other tilings have fixed neighbourhoods.
+    pub fn kernel_offsets(&self) -> Option<&[(i64, i64)]> {
+        match self.kind {
+            GridKind::Square => Some(&self.kernel.offsets),
+            GridKind::Hexagonal | GridKind::Triangular => None,
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: f64) {
+        self.cell_size = cell_size;
+    }
+
+    pub fn width(&self, size: &FieldSize) -> f64 {
+        match self.kind {
+            GridKind::Square => size.width as f64 * self.cell_size,
+            GridKind::Hexagonal => (size.width as f64 + 0.5) * self.cell_size,
+            GridKind::Triangular => (size.width as f64 + 1.0) * self.triangle_side() / 2.0,
+        }
+    }
+
+    pub fn height(&self, size: &FieldSize) -> f64 {
+        match self.kind {
+            GridKind::Square => size.height as f64 * self.cell_size,
+            GridKind::Hexagonal => {
+                let radius = self.hex_radius();
+                (size.height as f64 - 1.0) * radius * 1.5 + radius * 2.0
+            }
+            GridKind::Triangular => size.height as f64 * self.triangle_height(),
+        }
+    }
+
+    pub fn neighbours(&self, point: Point<u32>, size: &FieldSize) -> Vec<Point<u32>> {
+        let offsets: &[(i64, i64)] = match self.kind {
+            GridKind::Square => &self.kernel.offsets,
+            GridKind::Hexagonal if point.y.is_multiple_of(2) => &HEX_NEIGHBOURS_EVEN_ROW,
+            GridKind::Hexagonal => &HEX_NEIGHBOURS_ODD_ROW,
+            GridKind::Triangular if is_triangle_up(point) => &TRIANGLE_NEIGHBOURS_UP,
+            GridKind::Triangular => &TRIANGLE_NEIGHBOURS_DOWN,
+        };
+        offsets
+            .iter()
+            .filter_map(|(dx, dy)| checked_point(point.x as i64 + dx, point.y as i64 + dy, size))
+            .collect()
+    }
+
+    /// Returns the cell under the given point or `None` if the point is outside of the field.
+    pub fn cell_at(&self, point: Point<f64>, size: &FieldSize) -> Option<Point<u32>> {
+        if point.x < 0.0 || point.y < 0.0 {
+            return None;
+        }
+        match self.kind {
+            GridKind::Square => checked_point(
+                (point.x / self.cell_size).floor() as i64,
+                (point.y / self.cell_size).floor() as i64,
+                size,
+            ),
+            GridKind::Hexagonal => {
+                let radius = self.hex_radius();
+                let x = point.x - self.cell_size / 2.0;
+                let y = point.y - radius;
+                let q = (3.0_f64.sqrt() / 3.0 * x - y / 3.0) / radius;
+                let r = (2.0 / 3.0 * y) / radius;
+                let (q, r) = round_axial(q, r);
+                checked_point(q + (r - (r & 1)) / 2, r, size)
+            }
+            GridKind::Triangular => {
+                let row = (point.y / self.triangle_height()).floor() as i64;
+                let strip = (point.x / (self.triangle_side() / 2.0)).floor() as i64;
+                // a half-side wide strip is split between two neighbouring triangles
+                [strip - 1, strip]
+                    .iter()
+                    .filter_map(|x| checked_point(*x, row, size))
+                    .find(|candidate| contains_point(&self.cell_outline(*candidate), point))
+            }
+        }
+    }
+
+    /// Columns and rows of cells which may intersect the given area.
+    pub fn visible_cells(&self, area: Rect, size: &FieldSize) -> (Range<u32>, Range<u32>) {
+        // column width, row height and how many extra cells stick into the area
+        let (column, row, overlap) = match self.kind {
+            GridKind::Square => (self.cell_size, self.cell_size, 0.0),
+            GridKind::Hexagonal => (self.cell_size, self.hex_radius() * 1.5, 1.0),
+            GridKind::Triangular => (self.triangle_side() / 2.0, self.triangle_height(), 2.0),
+        };
+        let range = |start: f64, length: f64, step: f64, limit: u32| {
+            let first = (start / step).floor() - overlap;
+            let last = ((start + length) / step).ceil() + overlap;
+            (first.max(0.0) as u32).min(limit)..(last.max(0.0) as u32).min(limit)
+        };
+        (
+            range(area.x, area.width, column, size.width),
+            range(area.y, area.height, row, size.height),
+        )
+    }
+
+    /// Square area in the middle of the cell used to place numbers, flags and mines.
+    pub fn cell_bounds(&self, point: Point<u32>) -> Rect {
+        match self.kind {
+            GridKind::Square => Rect::square(
+                point.x as f64 * self.cell_size,
+                point.y as f64 * self.cell_size,
+                self.cell_size,
+            ),
+            GridKind::Hexagonal | GridKind::Triangular => {
+                let center = self.cell_center(point);
+                Rect::square(
+                    center.x - self.cell_size / 2.0,
+                    center.y - self.cell_size / 2.0,
+                    self.cell_size,
+                )
+            }
+        }
+    }
+
+    /// Corners of the cell in clockwise order starting from the top (left) one.
+    pub fn cell_outline(&self, point: Point<u32>) -> Vec<[f64; 2]> {
+        match self.kind {
+            GridKind::Square => {
+                let rect = self.cell_bounds(point);
+                vec![
+                    [rect.x, rect.y],
+                    [rect.x + rect.width, rect.y],
+                    [rect.x + rect.width, rect.y + rect.height],
+                    [rect.x, rect.y + rect.height],
+                ]
+            }
+            GridKind::Hexagonal => {
+                let center = self.cell_center(point);
+                let radius = self.hex_radius();
+                (0..6)
+                    .map(|i| {
+                        let angle = (60.0 * i as f64 - 90.0).to_radians();
+                        [
+                            center.x + radius * angle.cos(),
+                            center.y + radius * angle.sin(),
+                        ]
+                    })
+                    .collect()
+            }
+            GridKind::Triangular => {
+                let left = point.x as f64 * self.triangle_side() / 2.0;
+                let top = point.y as f64 * self.triangle_height();
+                let bottom = top + self.triangle_height();
+                if is_triangle_up(point) {
+                    vec![
+                        [left + self.triangle_side() / 2.0, top],
+                        [left + self.triangle_side(), bottom],
+                        [left, bottom],
+                    ]
+                } else {
+                    vec![
+                        [left, top],
+                        [left + self.triangle_side(), top],
+                        [left + self.triangle_side() / 2.0, bottom],
+                    ]
+                }
+            }
+        }
+    }
+
+    pub fn cell_center(&self, point: Point<u32>) -> Point<f64> {
+        match self.kind {
+            GridKind::Square => Point {
+                x: (point.x as f64 + 0.5) * self.cell_size,
+                y: (point.y as f64 + 0.5) * self.cell_size,
+            },
+            GridKind::Hexagonal => {
+                let radius = self.hex_radius();
+                Point {
+                    x: (point.x as f64 + 0.5 + 0.5 * (point.y % 2) as f64) * self.cell_size,
+                    y: radius + point.y as f64 * radius * 1.5,
+                }
+            }
+            // center of the inscribed circle
+            GridKind::Triangular => Point {
+                x: (point.x as f64 + 1.0) * self.triangle_side() / 2.0,
+                y: if is_triangle_up(point) {
+                    (point.y as f64 + 2.0 / 3.0) * self.triangle_height()
+                } else {
+                    (point.y as f64 + 1.0 / 3.0) * self.triangle_height()
+                },
+            },
+        }
+    }
+
+    // pointy top hexagon which is cell_size wide
+    fn hex_radius(&self) -> f64 {
+        self.cell_size / 3.0_f64.sqrt()
+    }
+
+    // equilateral triangle whose inscribed circle is cell_size wide
+    fn triangle_side(&self) -> f64 {
+        self.cell_size * 3.0_f64.sqrt()
+    }
+
+    fn triangle_height(&self) -> f64 {
+        self.cell_size * 1.5
+    }
+}
+
+fn is_triangle_up(point: Point<u32>) -> bool {
+    (point.x + point.y).is_multiple_of(2)
+}
+
+// works for convex outlines in clockwise order
+fn contains_point(outline: &[[f64; 2]], point: Point<f64>) -> bool {
+    (0..outline.len()).all(|i| {
+        let from = outline[i];
+        let to = outline[(i + 1) % outline.len()];
+        (to[0] - from[0]) * (point.y - from[1]) - (to[1] - from[1]) * (point.x - from[0]) >= 0.0
+    })
+}
+
+fn round_axial(q: f64, r: f64) -> (i64, i64) {
+    let s = -q - r;
+    let mut rq = q.round();
+    let mut rr = r.round();
+    let rs = s.round();
+    let dq = (rq - q).abs();
+    let dr = (rr - r).abs();
+    let ds = (rs - s).abs();
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    (rq as i64, rr as i64)
+}
+
+fn checked_point(x: i64, y: i64, size: &FieldSize) -> Option<Point<u32>> {
+    if x < 0 || y < 0 || x >= size.width as i64 || y >= size.height as i64 {
+        None
+    } else {
+        Some(Point {
+            x: x as u32,
+            y: y as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: FieldSize = FieldSize {
+        width: 7,
+        height: 6,
+        mines: 1,
+    };
+
+    fn cells() -> impl Iterator<Item = Point<u32>> {
+        (0..SIZE.height).flat_map(|y| (0..SIZE.width).map(move |x| Point { x, y }))
+    }
+
+    fn neighbour_count(geometry: &Geometry, x: u32, y: u32) -> usize {
+        geometry.neighbours(Point { x, y }, &SIZE).len()
+    }
+
+    // the centre and the points just inside every corner belong to the cell
+    fn assert_cells_are_hit(geometry: &Geometry) {
+        for cell in cells() {
+            let center = geometry.cell_center(cell);
+            assert_eq!(geometry.cell_at(center, &SIZE), Some(cell));
+            for [x, y] in geometry.cell_outline(cell) {
+                let near_corner = Point {
+                    x: x + (center.x - x) * 0.05,
+                    y: y + (center.y - y) * 0.05,
+                };
+                assert_eq!(
+                    geometry.cell_at(near_corner, &SIZE),
+                    Some(cell),
+                    "corner {},{}",
+                    x,
+                    y
+                );
+            }
+        }
+        let outside = [
+            Point { x: -1.0, y: 1.0 },
+            Point { x: 1.0, y: -1.0 },
+            Point {
+                x: geometry.width(&SIZE) + 1.0,
+                y: 1.0,
+            },
+            Point {
+                x: 1.0,
+                y: geometry.height(&SIZE) + 1.0,
+            },
+        ];
+        for point in outside {
+            assert_eq!(geometry.cell_at(point, &SIZE), None);
+        }
+    }
+
+    fn assert_neighbours_are_symmetric(geometry: &Geometry) {
+        for cell in cells() {
+            for neighbour in geometry.neighbours(cell, &SIZE) {
+                assert_ne!(neighbour, cell);
+                assert!(
+                    geometry.neighbours(neighbour, &SIZE).contains(&cell),
+                    "{:?} is a neighbour of {:?} but not the other way",
+                    neighbour,
+                    cell
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn named_kernels_are_parsed() {
+        assert_eq!(Kernel::parse("moore"), Ok(Kernel::default()));
+        assert_eq!(Kernel::parse("orthogonal").unwrap().offsets.len(), 4);
+        assert_eq!(Kernel::parse("knight").unwrap().offsets.len(), 8);
+        assert_eq!(Kernel::parse("extended").unwrap().offsets.len(), 24);
+    }
+
+    #[test]
+    fn custom_kernels_are_parsed() {
+        let kernel = Kernel::parse("0,-1; 0,1;-2,0;2,0;2,0").unwrap();
+        assert_eq!(kernel.offsets, vec![(0, -1), (0, 1), (-2, 0), (2, 0)]);
+        let far = Kernel::parse("3,-3;-3,3").unwrap();
+        assert_eq!(far.offsets, vec![(3, -3), (-3, 3)]);
+    }
+
+    #[test]
+    fn kernels_are_written_like_they_are_parsed() {
+        for spec in [
+            "moore",
+            "orthogonal",
+            "knight",
+            "extended",
+            "0,-1;0,1;-2,0;2,0",
+        ] {
+            assert_eq!(Kernel::parse(spec).unwrap().spec(), spec);
+        }
+    }
+
+    #[test]
+    fn bad_kernels_are_rejected() {
+        assert!(Kernel::parse("").is_err());
+        assert!(Kernel::parse("1").is_err());
+        assert!(Kernel::parse("1,x").is_err());
+        assert!(Kernel::parse("1,0;").is_err());
+        assert!(Kernel::parse("0,0;1,0").is_err());
+        assert!(Kernel::parse("4,0").is_err());
+        assert!(Kernel::parse("0,-4").is_err());
+        assert!(Kernel::parse(&format!("{},0", i64::MIN)).is_err());
+        // every offset within 3 cells is more than 24
+        let mut offsets = Vec::new();
+        for dy in -3..=3 {
+            for dx in -3..=3 {
+                if (dx, dy) != (0, 0) {
+                    offsets.push(format!("{},{}", dx, dy));
+                }
+            }
+        }
+        assert!(Kernel::parse(&offsets[..24].join(";")).is_ok());
+        assert!(Kernel::parse(&offsets[..25].join(";")).is_err());
+    }
+
+    #[test]
+    fn kernels_of_other_players_are_checked() {
+        let kernel: Kernel = serde_json::from_str("[[0,-1],[2,1]]").unwrap();
+        assert_eq!(kernel.offsets, vec![(0, -1), (2, 1)]);
+        assert_eq!(serde_json::to_string(&kernel).unwrap(), "[[0,-1],[2,1]]");
+        assert!(serde_json::from_str::<Kernel>("[[0,0]]").is_err());
+        assert!(serde_json::from_str::<Kernel>("[[0,9]]").is_err());
+    }
+
+    #[test]
+    fn square_cells_are_hit() {
+        assert_cells_are_hit(&Geometry::new(GridKind::Square, Kernel::default()));
+    }
+
+    #[test]
+    fn square_neighbours() {
+        let geometry = Geometry::new(GridKind::Square, Kernel::default());
+        assert_neighbours_are_symmetric(&geometry);
+        assert_eq!(neighbour_count(&geometry, 3, 3), 8);
+        assert_eq!(neighbour_count(&geometry, 3, 0), 5);
+        assert_eq!(neighbour_count(&geometry, 6, 5), 3);
+    }
+
+    #[test]
+    fn hex_cells_are_hit() {
+        assert_cells_are_hit(&Geometry::new(GridKind::Hexagonal, Kernel::default()));
+    }
+
+    #[test]
+    fn hex_neighbours() {
+        let geometry = Geometry::new(GridKind::Hexagonal, Kernel::default());
+        assert_neighbours_are_symmetric(&geometry);
+        // odd rows are shifted to the right, so even and odd rows differ at the edges
+        assert_eq!(neighbour_count(&geometry, 3, 2), 6);
+        assert_eq!(neighbour_count(&geometry, 3, 3), 6);
+        assert_eq!(neighbour_count(&geometry, 3, 0), 4);
+        assert_eq!(neighbour_count(&geometry, 0, 2), 3);
+        assert_eq!(neighbour_count(&geometry, 0, 1), 5);
+        assert_eq!(neighbour_count(&geometry, 6, 2), 5);
+        assert_eq!(neighbour_count(&geometry, 6, 1), 3);
+        assert_eq!(neighbour_count(&geometry, 0, 0), 2);
+        assert_eq!(neighbour_count(&geometry, 6, 0), 3);
+        assert_eq!(neighbour_count(&geometry, 0, 5), 3);
+        assert_eq!(neighbour_count(&geometry, 6, 5), 2);
+        // axial rounding of a point next to a shared corner picks the nearest centre
+        let center = geometry.cell_center(Point { x: 2, y: 3 });
+        let toward = geometry.cell_center(Point { x: 3, y: 4 });
+        let point = Point {
+            x: center.x + (toward.x - center.x) * 0.45,
+            y: center.y + (toward.y - center.y) * 0.45,
+        };
+        assert_eq!(geometry.cell_at(point, &SIZE), Some(Point { x: 2, y: 3 }));
+    }
+
+    #[test]
+    fn triangle_cells_are_hit() {
+        assert_cells_are_hit(&Geometry::new(GridKind::Triangular, Kernel::default()));
+    }
+
+    #[test]
+    fn triangle_neighbours() {
+        let geometry = Geometry::new(GridKind::Triangular, Kernel::default());
+        assert_neighbours_are_symmetric(&geometry);
+        assert_eq!(neighbour_count(&geometry, 3, 3), 12);
+        assert_eq!(neighbour_count(&geometry, 4, 3), 12);
+        // pointing up on the top edge still has the row below, pointing down doesn't
+        assert_eq!(neighbour_count(&geometry, 2, 0), 9);
+        assert_eq!(neighbour_count(&geometry, 3, 0), 7);
+        assert_eq!(neighbour_count(&geometry, 0, 2), 7);
+        assert_eq!(neighbour_count(&geometry, 0, 3), 7);
+        assert_eq!(neighbour_count(&geometry, 0, 0), 5);
+        assert_eq!(neighbour_count(&geometry, 1, 0), 6);
+        assert_eq!(neighbour_count(&geometry, 6, 5), 5);
+        // both triangles of a half-side wide strip are told apart by their shared edge
+        let up = geometry.cell_outline(Point { x: 2, y: 2 });
+        let down = geometry.cell_center(Point { x: 3, y: 2 });
+        let edge_middle = Point {
+            x: (up[0][0] + up[1][0]) / 2.0,
+            y: (up[0][1] + up[1][1]) / 2.0,
+        };
+        let past_edge = Point {
+            x: edge_middle.x + (down.x - edge_middle.x) * 0.05,
+            y: edge_middle.y + (down.y - edge_middle.y) * 0.05,
+        };
+        assert_eq!(
+            geometry.cell_at(past_edge, &SIZE),
+            Some(Point { x: 3, y: 2 })
+        );
+    }
+}
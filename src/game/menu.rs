@@ -1,35 +1,61 @@
 use crate::game::draw::{
-    draw_counter, draw_menu_button, draw_menu_button_pressed, draw_timer, DrawData, Rect,
+    draw_counter, draw_lives, draw_menu_background, draw_menu_button, draw_menu_button_pressed,
+    draw_timer, DrawData, Rect,
 };
+use crate::game::GameElement;
 use crate::game::Point;
-use crate::GameElement;
-use graphics::color::BLACK;
-use graphics::types::Rectangle;
 use graphics::Context;
 use opengl_graphics::GlGraphics;
 use piston::{Button, ButtonArgs, ButtonState, MouseButton, RenderArgs, UpdateArgs};
 
 pub const MENU_HEIGHT: f64 = 50.0;
-const MENU_BLUE: [f32; 4] = [0.273, 0.384, 0.940, 1.0];
 
 pub const GAME_OK: &str = ":-)";
 pub const GAME_WIN: &str = "B-)";
 pub const GAME_LOOSE: &str = "X-(";
 
+// the face on the new game button
+#[derive(Copy, Clone)]
+enum Face {
+    Ok,
+    Win,
+    Loose,
+}
+
+impl Face {
+    fn text(self) -> &'static str {
+        match self {
+            Face::Ok => GAME_OK,
+            Face::Win => GAME_WIN,
+            Face::Loose => GAME_LOOSE,
+        }
+    }
+
+    fn sprite(self) -> &'static str {
+        match self {
+            Face::Ok => "face_ok",
+            Face::Win => "face_win",
+            Face::Loose => "face_loose",
+        }
+    }
+}
+
 pub enum MenuButtonPressResult {
     NoAction,
     NewGame,
+    TogglePause,
 }
 
 struct GameButton {
     rect: Rect,
-    text: String,
+    face: Face,
     pressed: bool,
 }
 
 struct Timer {
     time: f64,
     runnnig: bool,
+    paused: bool,
     rect: Rect,
 }
 
@@ -38,52 +64,78 @@ struct MineCounter {
     rect: Rect,
 }
 
+// shown only when hitting a mine costs a life instead of the game
+struct Lives {
+    lives: Option<(u32, u32)>,
+    rect: Rect,
+}
+
 pub struct Menu {
     rect: Rect,
     game_button: GameButton,
     timer: Timer,
     mine_counter: MineCounter,
+    lives: Lives,
 }
 
 impl Menu {
     pub fn new(width: f64, mines: u32) -> Menu {
-        Menu {
+        let mut menu = Menu {
             rect: Rect::new(0.0, 0.0, width, MENU_HEIGHT),
             game_button: GameButton {
-                rect: Rect::square(
-                    width / 2.0 - MENU_HEIGHT * 0.4,
-                    MENU_HEIGHT * 0.1,
-                    MENU_HEIGHT * 0.8,
-                ),
-                text: String::from(GAME_OK),
+                rect: Rect::square(0.0, 0.0, 0.0),
+                face: Face::Ok,
                 pressed: false,
             },
             timer: Timer {
                 time: 0.0,
                 runnnig: false,
-                rect: Rect::new(
-                    MENU_HEIGHT * 0.1,
-                    MENU_HEIGHT * 0.1,
-                    MENU_HEIGHT * 2.0,
-                    MENU_HEIGHT * 0.8,
-                ),
+                paused: false,
+                rect: Rect::square(0.0, 0.0, 0.0),
             },
             mine_counter: MineCounter {
                 mines,
-                rect: Rect::new(
-                    width - MENU_HEIGHT * 0.1 - MENU_HEIGHT * 2.0,
-                    MENU_HEIGHT * 0.1,
-                    MENU_HEIGHT * 2.0,
-                    MENU_HEIGHT * 0.8,
-                ),
+                rect: Rect::square(0.0, 0.0, 0.0),
             },
-        }
+            lives: Lives {
+                lives: None,
+                rect: Rect::square(0.0, 0.0, 0.0),
+            },
+        };
+        menu.resize(width, MENU_HEIGHT);
+        menu
+    }
+
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.rect = Rect::new(0.0, 0.0, width, height);
+        self.game_button.rect =
+            Rect::square(width / 2.0 - height * 0.4, height * 0.1, height * 0.8);
+        self.timer.rect = Rect::new(height * 0.1, height * 0.1, height * 2.0, height * 0.8);
+        self.mine_counter.rect = Rect::new(
+            width - height * 0.1 - height * 2.0,
+            height * 0.1,
+            height * 2.0,
+            height * 0.8,
+        );
+        // between the new game button and the mine counter, narrow menus have no room
+        // there and get a badge on the corner of the button
+        let lives_x = width / 2.0 + height * 0.6;
+        let lives_width = self.mine_counter.rect.x - height * 0.2 - lives_x;
+        self.lives.rect = if lives_width >= height * 0.6 {
+            Rect::new(lives_x, height * 0.2, lives_width, height * 0.6)
+        } else {
+            Rect::square(width / 2.0 + height * 0.05, height * 0.5, height * 0.4)
+        };
     }
 }
 
 impl Menu {
     pub fn height(&self) -> f64 {
-        MENU_HEIGHT
+        self.rect.height
+    }
+
+    pub fn time(&self) -> f64 {
+        self.timer.time
     }
 }
 
@@ -94,21 +146,21 @@ impl Menu {
         button_args: &ButtonArgs,
         point: Point<f64>,
     ) -> MenuButtonPressResult {
+        if button_args.button != Button::from(MouseButton::Left) {
+            return MenuButtonPressResult::NoAction;
+        }
+        // timer doubles as the pause button
+        if self.timer.rect.contains_point(point) && button_args.state == ButtonState::Release {
+            return MenuButtonPressResult::TogglePause;
+        }
+        if !self.game_button.rect.contains_point(point) {
+            return MenuButtonPressResult::NoAction;
+        }
         if button_args.state == ButtonState::Release {
-            if button_args.button == Button::from(MouseButton::Left) {
-                if self.game_button.rect.contains_point(point) {
-                    self.game_button.pressed = false;
-                    self.timer.runnnig = false;
-                    self.timer.time = 0.0;
-                    return MenuButtonPressResult::NewGame;
-                }
-            }
+            self.game_button.pressed = false;
+            return MenuButtonPressResult::NewGame;
         } else if button_args.state == ButtonState::Press {
-            if button_args.button == Button::from(MouseButton::Left) {
-                if self.game_button.rect.contains_point(point) {
-                    self.game_button.pressed = true;
-                }
-            }
+            self.game_button.pressed = true;
         }
         MenuButtonPressResult::NoAction
     }
@@ -119,41 +171,52 @@ impl Menu {
         self.timer.runnnig = true;
     }
 
+    pub fn reset_timer(&mut self) {
+        self.timer.runnnig = false;
+        self.timer.time = 0.0;
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        self.timer.time = time;
+    }
+
     pub fn stop_timer(&mut self) {
         self.timer.runnnig = false;
     }
 
+    pub fn set_paused(&mut self, paused: bool) {
+        self.timer.paused = paused;
+    }
+
     pub fn set_mines(&mut self, mines: u32) {
         self.mine_counter.mines = mines;
     }
 
+    /// Lives left and the lives of a game, `None` when the first mine ends it.
+    pub fn set_lives(&mut self, lives: Option<(u32, u32)>) {
+        self.lives.lives = lives;
+    }
+
     pub fn set_ok(&mut self) {
-        self.game_button.text = String::from(GAME_OK);
+        self.game_button.face = Face::Ok;
     }
 
     pub fn set_win(&mut self) {
-        self.game_button.text = String::from(GAME_WIN);
+        self.game_button.face = Face::Win;
     }
 
     pub fn set_loose(&mut self) {
-        self.game_button.text = String::from(GAME_LOOSE);
+        self.game_button.face = Face::Loose;
     }
 }
 
 impl GameElement for Menu {
     fn render(&self, args: &RenderArgs, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
-        graphics::rectangle(MENU_BLUE, self.rect.to_arr(), c.transform, gl);
-        graphics::line_from_to(
-            BLACK,
-            2.0,
-            [0.0, self.rect.height - 1.0],
-            [self.rect.width, self.rect.height - 1.0],
-            c.transform,
-            gl,
-        );
+        draw_menu_background(self.rect, c, gl, dd);
         self.game_button.render(args, c, gl, dd);
         self.timer.render(args, c, gl, dd);
         self.mine_counter.render(args, c, gl, dd);
+        self.lives.render(args, c, gl, dd);
     }
 
     fn update(&mut self, _update_args: &UpdateArgs) {
@@ -170,9 +233,9 @@ impl GameElement for GameButton {
         dd: &mut DrawData,
     ) {
         if self.pressed {
-            draw_menu_button_pressed(&self.text, self.rect, c, gl, dd);
+            draw_menu_button_pressed(self.face.text(), self.rect, c, gl, dd);
         } else {
-            draw_menu_button(&self.text, self.rect, c, gl, dd);
+            draw_menu_button(self.face.text(), self.face.sprite(), self.rect, c, gl, dd);
         }
     }
 
@@ -187,7 +250,7 @@ impl GameElement for Timer {
         gl: &mut GlGraphics,
         dd: &mut DrawData,
     ) {
-        draw_timer(self.rect, self.time, c, gl, dd);
+        draw_timer(self.rect, self.time, self.paused, c, gl, dd);
     }
 
     fn update(&mut self, update_args: &UpdateArgs) {
@@ -210,3 +273,19 @@ impl GameElement for MineCounter {
 
     fn update(&mut self, _update_args: &UpdateArgs) {}
 }
+
+impl GameElement for Lives {
+    fn render(
+        &self,
+        _render_args: &RenderArgs,
+        c: Context,
+        gl: &mut GlGraphics,
+        dd: &mut DrawData,
+    ) {
+        if let Some((left, total)) = self.lives {
+            draw_lives(self.rect, left, total, c, gl, dd);
+        }
+    }
+
+    fn update(&mut self, _update_args: &UpdateArgs) {}
+}
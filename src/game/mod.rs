@@ -1,151 +1,513 @@
-use graphics::color::WHITE;
 use graphics::Context;
 use graphics::Transformed;
 use opengl_graphics::GlGraphics;
-use piston::{Button, ButtonArgs, ButtonState, MouseButton, RenderArgs};
+use piston::{Button, ButtonArgs, ButtonState, Key, MouseButton, RenderArgs};
+use serde::{Deserialize, Serialize};
 
-use crate::game::draw::DrawData;
-use crate::UpdateArgs;
+use crate::game::camera::Camera;
+use crate::game::cell::CellView;
 
-mod cell;
-pub(crate) mod draw;
-pub(crate) mod field;
+use crate::game::draw::{DrawData, Rect};
+use crate::game::events::{EventBus, GameEvent, GameObserver};
+use crate::game::geometry::Geometry;
+use crate::game::settings::Settings;
+use crate::game::theme::Color;
+use piston::UpdateArgs;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod animation;
+pub mod assets;
+pub mod bot;
+mod camera;
+pub mod cell;
+pub mod coop;
+pub mod daily;
+pub mod draw;
+pub mod events;
+pub mod field;
+pub mod generator;
+pub mod geometry;
 pub(crate) mod menu;
+pub(crate) mod net;
+pub mod puzzle;
+pub mod race;
+pub mod settings;
+pub mod skin;
+pub mod sound;
+pub mod spectate;
+pub mod theme;
 
-pub const CELL_SIZE: f64 = 30.0;
+pub const DEFAULT_CELL_SIZE: f64 = 30.0;
+const MIN_CELL_SIZE: f64 = 10.0;
+// initial window is capped so large boards still fit on the screen
+const MAX_INITIAL_WIDTH: f64 = 1280.0;
+const MAX_INITIAL_HEIGHT: f64 = 900.0;
+const MINIMAP_SIZE: f64 = 150.0;
+const MINIMAP_MARGIN: f64 = 10.0;
+const KEYBOARD_PAN_CELLS: f64 = 3.0;
+// middle button moved further than this pans instead of chording
+const PAN_THRESHOLD: f64 = 4.0;
+const SCOREBOARD_MARGIN: f64 = 10.0;
 
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FieldSize {
     pub width: u32,
     pub height: u32,
     pub mines: u32,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Point<T> {
     pub x: T,
     pub y: T,
 }
 
-impl Point<u32> {
-    pub fn to_f64(&self) -> Point<f64> {
-        Point {
-            x: self.x as f64,
-            y: self.y as f64,
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GameState {
     Ready,
     Playing,
+    Paused,
+    #[serde(rename = "won")]
     Win,
+    #[serde(rename = "lost")]
     Loose,
 }
 
 pub enum CellInteractionResult {
     NoAction,
     Opened,
+    Chorded,
     Flagged,
     Unflagged,
     Exploded,
 }
 
+impl CellInteractionResult {
+    /// Name used by the text protocols.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CellInteractionResult::NoAction => "no_action",
+            CellInteractionResult::Opened => "opened",
+            CellInteractionResult::Chorded => "chorded",
+            CellInteractionResult::Flagged => "flagged",
+            CellInteractionResult::Unflagged => "unflagged",
+            CellInteractionResult::Exploded => "exploded",
+        }
+    }
+}
+
 pub struct Game {
     field: field::Field,
     menu: menu::Menu,
     mouse_position: Option<Point<f64>>,
+    camera: Camera,
+    // last cursor position while the field is dragged with the middle button
+    pan_position: Option<Point<f64>>,
+    // where the middle button was pressed, until the cursor moves far enough to pan
+    middle_press: Option<Point<f64>>,
     game_state: GameState,
     button_press_counter: u8,
     both_buttons_flag: bool,
+    // the press of the left button follows the cursor
+    left_down: bool,
+    events: EventBus,
+    // games are started from outside, like by the host of a race
+    race: bool,
+    // other players see the board, so it can't be hidden by a pause
+    shared: bool,
+    // the board is owned by another player, who makes the moves
+    remote: bool,
+    moves: Vec<Move>,
+    seed: Option<u64>,
+    // how every new game is set up before the first move
+    deal: Option<Deal>,
+    // every mine hit costs a life, the game is lost with the last one
+    lives: Option<u32>,
+    // shown over the field, like the standings of a race
+    scoreboard: Vec<String>,
+    // cells under the cursors of the other players
+    cursors: Vec<(Point<u32>, Color)>,
+    // cells changed by the last update, for the players who watch or share the board
+    changes: field::Changes,
 }
 
 impl Game {
-    pub fn new(size: FieldSize) -> Game {
-        let field = field::Field::new(size);
-        let width = field.width() as f64;
+    pub fn new(settings: Settings) -> Game {
+        let mut field = field::Field::new(
+            settings.field_size,
+            Geometry::new(settings.grid, settings.kernel),
+            settings.mines_per_cell,
+            settings.click_chord,
+            settings.hover,
+            settings.animations,
+        );
+        field.set_seed(settings.seed);
+        let width = field.width();
+        let height = field.height();
         let mines = field.mines();
+        let mut menu = menu::Menu::new(width, mines);
+        menu.set_lives(settings.lives.map(|lives| (lives, lives)));
+        let camera = Camera::new(
+            Rect::new(0.0, menu::MENU_HEIGHT, width, height),
+            field.cell_size(),
+            [width / field.cell_size(), height / field.cell_size()],
+        );
         Game {
             field,
-            menu: menu::Menu::new(width, mines),
+            menu,
             mouse_position: None,
+            camera,
+            pan_position: None,
+            middle_press: None,
             game_state: GameState::Ready,
             button_press_counter: 0,
             both_buttons_flag: false,
+            left_down: false,
+            events: EventBus::default(),
+            race: false,
+            shared: false,
+            remote: false,
+            moves: Vec::new(),
+            seed: settings.seed,
+            deal: None,
+            lives: settings.lives,
+            scoreboard: Vec::new(),
+            cursors: Vec::new(),
+            changes: field::Changes::default(),
         }
     }
 }
 
 impl Game {
     pub fn height(&self) -> f64 {
-        self.field.height() + self.menu.height()
+        (self.field.height() + self.menu.height()).min(MAX_INITIAL_HEIGHT)
     }
 
     pub fn width(&self) -> f64 {
-        self.field.width()
+        self.field.width().min(MAX_INITIAL_WIDTH)
     }
 
-    pub fn game_state(&self) -> GameState {
-        self.game_state
+    /// Picks the largest cell size which fits the whole board into the window,
+    /// the menu is scaled along with the cells. Boards which don't fit even with
+    /// the smallest cells have to be panned.
+    pub fn resize(&mut self, window_size: [f64; 2]) {
+        let [width, height] = window_size;
+        let cell_size = self.field.cell_size();
+        let cells_wide = self.field.width() / cell_size;
+        let cells_high = self.field.height() / cell_size + menu::MENU_HEIGHT / DEFAULT_CELL_SIZE;
+        let fit_cell_size = (width / cells_wide)
+            .min(height / cells_high)
+            .max(MIN_CELL_SIZE);
+        let menu_height = fit_cell_size * menu::MENU_HEIGHT / DEFAULT_CELL_SIZE;
+        self.menu.resize(width, menu_height);
+        self.camera.set_view(
+            Rect::new(0.0, menu_height, width, height - menu_height),
+            fit_cell_size,
+        );
+        self.apply_camera();
+    }
+
+    pub fn mouse_scroll(&mut self, scroll: &[f64; 2]) {
+        if let Some(point) = self.mouse_position {
+            if scroll[1] > 0.0 {
+                self.camera.zoom_in(point);
+            } else if scroll[1] < 0.0 {
+                self.camera.zoom_out(point);
+            }
+            self.apply_camera();
+        }
+    }
+
+    fn apply_camera(&mut self) {
+        self.field.set_cell_size(self.camera.cell_size());
+        self.field
+            .set_visible_area(Some(self.camera.visible_area()));
+        if let Some(position) = self.mouse_position {
+            self.field.set_hovered(self.cell_under(position));
+        }
+    }
+
+    // arrows pan, +/- zoom around the middle of the view, 0 fits the whole board
+    fn camera_key(&mut self, key: Key) -> bool {
+        let step = self.camera.cell_size() * KEYBOARD_PAN_CELLS;
+        let view = self.camera.view();
+        let center = Point {
+            x: view.x + view.width / 2.0,
+            y: view.y + view.height / 2.0,
+        };
+        match key {
+            Key::Left => self.camera.pan(step, 0.0),
+            Key::Right => self.camera.pan(-step, 0.0),
+            Key::Up => self.camera.pan(0.0, step),
+            Key::Down => self.camera.pan(0.0, -step),
+            Key::Equals | Key::NumPadPlus => self.camera.zoom_in(center),
+            Key::Minus | Key::NumPadMinus => self.camera.zoom_out(center),
+            Key::D0 | Key::NumPad0 => self.camera.reset_zoom(),
+            _ => return false,
+        }
+        self.apply_camera();
+        true
     }
 }
 
 impl GameElement for Game {
     fn render(&self, args: &RenderArgs, c: Context, gl: &mut GlGraphics, dd: &mut DrawData) {
-        graphics::clear(WHITE, gl);
-        self.menu.render(&args, c, gl, dd);
+        graphics::clear(dd.theme.background, gl);
+        let origin = self.camera.origin();
         let field_render_context = Context {
-            transform: c.transform.trans(0.0, self.menu.height() as f64),
+            transform: c.transform.trans(origin.x, origin.y),
             ..c
         };
-        self.field.render(&args, field_render_context, gl, dd);
+        if self.game_state == GameState::Paused {
+            // board is hidden so it can't be studied while the timer is stopped
+            let rect = Rect::new(0.0, 0.0, self.field.width(), self.field.height());
+            draw::draw_pause_cover(rect, field_render_context, gl, dd);
+        } else {
+            self.field.render(args, field_render_context, gl, dd);
+            for &(cell, color) in &self.cursors {
+                draw::draw_cursor(
+                    self.field.geometry(),
+                    cell,
+                    color,
+                    field_render_context.transform,
+                    gl,
+                );
+            }
+            if !self.camera.shows_whole_field() {
+                self.field.render_minimap(
+                    self.minimap_rect(),
+                    self.camera.visible_area(),
+                    &dd.theme,
+                    c,
+                    gl,
+                );
+            }
+        }
+        if !self.scoreboard.is_empty() {
+            let view = self.camera.view();
+            let position = Point {
+                x: view.x + SCOREBOARD_MARGIN,
+                y: view.y + SCOREBOARD_MARGIN,
+            };
+            let line_height = self.menu.height() * 0.4;
+            draw::draw_scoreboard(&self.scoreboard, position, line_height, c, gl, dd);
+        }
+        // the menu covers the field when it is panned under it
+        self.menu.render(args, c, gl, dd);
     }
     fn update(&mut self, args: &UpdateArgs) {
-        self.field.update(&args);
-        self.menu.update(&args);
+        let second = self.menu.time().floor();
+        self.field.update(args);
+        self.changes = self.field.take_changes();
+        self.menu.update(args);
+        if self.game_state == GameState::Playing && self.menu.time().floor() > second {
+            self.events.emit(GameEvent::TimerTick {
+                seconds: self.menu.time() as u32,
+            });
+        }
     }
 }
 
 impl Game {
     pub fn mouse_move(&mut self, mouse_args: &[f64]) {
-        self.mouse_position = Some(Point {
+        let position = Point {
             x: mouse_args[0],
             y: mouse_args[1],
-        });
+        };
+        if let Some(start) = self.middle_press {
+            if (position.x - start.x).hypot(position.y - start.y) <= PAN_THRESHOLD {
+                self.mouse_position = Some(position);
+                return;
+            }
+            self.middle_press = None;
+            self.field.release_pressed();
+        }
+        if let Some(last) = self.pan_position {
+            self.camera.pan(position.x - last.x, position.y - last.y);
+            self.apply_camera();
+            self.pan_position = Some(position);
+        }
+        self.mouse_position = Some(position);
+        self.track_cursor(position);
+    }
+
+    // moves the hover highlight and the press of held buttons to the cell under the cursor
+    fn track_cursor(&mut self, position: Point<f64>) {
+        let cell_point = self.cell_under(position);
+        self.field.set_hovered(cell_point);
+        if self.game_state != GameState::Ready && self.game_state != GameState::Playing {
+            return;
+        }
+        let chord = self.both_buttons_flag && self.button_press_counter == 2;
+        if !chord && !self.left_down {
+            return;
+        }
+        match cell_point {
+            Some(cell_point) if chord => self.field.press_chord(cell_point),
+            Some(cell_point) => self.field.press_cell(cell_point),
+            None => self.field.release_pressed(),
+        }
+    }
+
+    fn cell_under(&self, point: Point<f64>) -> Option<Point<u32>> {
+        if point.y < self.menu.height() {
+            return None;
+        }
+        let origin = self.camera.origin();
+        self.field.cell_under(Point {
+            x: point.x - origin.x,
+            y: point.y - origin.y,
+        })
+    }
+
+    /// Sound, statistics and other listeners get every game event from here.
+    pub fn subscribe(&mut self, observer: Rc<RefCell<dyn GameObserver>>) {
+        self.events.subscribe(observer);
+    }
+
+    pub fn focus(&mut self, focused: bool) {
+        if !focused && self.can_pause() && self.game_state == GameState::Playing {
+            self.switch_state(GameState::Paused);
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if !self.can_pause() {
+            return;
+        }
+        match self.game_state {
+            GameState::Playing => self.switch_state(GameState::Paused),
+            GameState::Paused => self.switch_state(GameState::Playing),
+            _ => {}
+        }
     }
 
     pub fn button_action(&mut self, args: &ButtonArgs) {
+        // any click or key shows the end of running animations, the input itself still counts
+        if args.state == ButtonState::Press && self.field.is_animating() {
+            self.field.skip_animations();
+        }
+        if args.button == Button::from(MouseButton::Left) {
+            self.left_down = args.state == ButtonState::Press;
+        }
+        // buttons are counted wherever they are released, a press dragged over the menu
+        // or out of the window would otherwise turn the next click into a chord
+        if args.button == Button::from(MouseButton::Left)
+            || args.button == Button::from(MouseButton::Right)
+        {
+            match args.state {
+                ButtonState::Press => {
+                    self.button_press_counter += 1;
+                    if self.button_press_counter == 2 {
+                        self.both_buttons_flag = true;
+                    }
+                }
+                ButtonState::Release => {
+                    self.button_press_counter = self.button_press_counter.saturating_sub(1);
+                }
+            }
+        }
+        if args.button == Button::Keyboard(Key::P) {
+            if args.state == ButtonState::Release {
+                self.toggle_pause();
+            }
+            return;
+        }
+        if let Button::Keyboard(key) = args.button {
+            if args.state == ButtonState::Press && self.camera_key(key) {
+                return;
+            }
+        }
+        // middle click chords, middle drag pans
+        if args.button == Button::from(MouseButton::Middle) {
+            let click = match args.state {
+                ButtonState::Press => {
+                    self.pan_position = self.mouse_position;
+                    self.middle_press = self.mouse_position;
+                    true
+                }
+                ButtonState::Release => {
+                    self.pan_position = None;
+                    self.middle_press.take().is_some()
+                }
+            };
+            if !click {
+                return;
+            }
+        }
         if let Some(point) = self.mouse_position {
-            if point.y < self.menu.height() as f64 {
+            if point.y < self.menu.height() {
+                // a press dragged over the menu is cancelled
+                if args.state == ButtonState::Release {
+                    self.field.release_pressed();
+                }
                 let result = self.menu.button_action(args, point);
                 match result {
-                    menu::MenuButtonPressResult::NewGame => {
-                        self.switch_state(GameState::Ready);
+                    menu::MenuButtonPressResult::NewGame if !self.race => {
+                        self.new_game();
+                    }
+                    menu::MenuButtonPressResult::TogglePause => {
+                        self.toggle_pause();
                     }
-                    menu::MenuButtonPressResult::NoAction => {}
+                    menu::MenuButtonPressResult::NewGame
+                    | menu::MenuButtonPressResult::NoAction => {}
                 }
             } else {
-                if self.game_state == GameState::Ready || self.game_state == GameState::Playing {
+                if (self.game_state == GameState::Ready && !self.race)
+                    || self.game_state == GameState::Playing
+                {
                     let result = self.button_action_field(args, point);
-                    match result {
-                        CellInteractionResult::Exploded => {
-                            self.switch_state(GameState::Loose);
-                            self.both_buttons_flag = false;
-                        }
-                        CellInteractionResult::Flagged | CellInteractionResult::Unflagged => {
-                            self.menu.set_mines(self.field.mines() - self.field.flags());
-                        }
-                        CellInteractionResult::Opened => {
-                            self.both_buttons_flag = false;
-                            if self.field.cells_left() == 0 {
-                                self.switch_state(GameState::Win);
-                            }
-                        }
-                        _ => {}
-                    }
+                    self.apply_result(&result);
                 }
             }
         }
+        if self.button_press_counter == 0 {
+            self.both_buttons_flag = false;
+        }
+    }
+
+    fn apply_result(&mut self, result: &CellInteractionResult) {
+        match result {
+            CellInteractionResult::Exploded if self.lives_left() > 0 => {
+                self.show_lives();
+                self.menu.set_mines(self.field.mines_left());
+                // a chord can open the last safe cells along with a mine
+                if self.field.cells_left() == 0 {
+                    self.switch_state(GameState::Win);
+                }
+            }
+            CellInteractionResult::Exploded => {
+                self.show_lives();
+                self.switch_state(GameState::Loose);
+                self.both_buttons_flag = false;
+            }
+            CellInteractionResult::Flagged | CellInteractionResult::Unflagged => {
+                // multi-mine cells make it easy to place more flags than mines
+                self.menu.set_mines(self.field.mines_left());
+            }
+            CellInteractionResult::Opened | CellInteractionResult::Chorded
+                if self.field.cells_left() == 0 =>
+            {
+                self.switch_state(GameState::Win);
+            }
+            _ => {}
+        }
+    }
+
+    fn minimap_rect(&self) -> Rect {
+        let [field_width, field_height] = self.camera.field_size();
+        let scale = MINIMAP_SIZE / field_width.max(field_height);
+        let view = self.camera.view();
+        let width = field_width * scale;
+        let height = field_height * scale;
+        Rect::new(
+            view.x + view.width - width - MINIMAP_MARGIN,
+            view.y + view.height - height - MINIMAP_MARGIN,
+            width,
+            height,
+        )
     }
 
     fn switch_state(&mut self, state: GameState) {
@@ -161,6 +523,9 @@ impl Game {
                 self.button_press_counter = 0;
                 self.both_buttons_flag = false;
             }
+            GameState::Paused => {
+                self.menu.set_paused(false);
+            }
             GameState::Win => {}
             GameState::Loose => {}
         }
@@ -170,20 +535,41 @@ impl Game {
         self.game_state = state;
         match self.game_state {
             GameState::Ready => {
+                self.menu.reset_timer();
                 self.field.reset();
+                self.deal();
                 self.menu.set_mines(self.field.mines());
+                self.show_lives();
                 self.menu.set_ok();
             }
             GameState::Playing => {
                 self.menu.start_timer();
-                self.menu.set_mines(self.field.mines());
+                self.menu.set_mines(self.field.mines_left());
                 self.menu.set_ok();
             }
+            GameState::Paused => {
+                self.menu.set_paused(true);
+            }
             GameState::Win => {
                 self.menu.set_win();
+                // a remote board shows the mines and flags of its owner
+                if !self.remote {
+                    self.field.flag_mines();
+                }
+                self.menu.set_mines(0);
+                self.field.celebrate();
+                self.events.emit(GameEvent::Won {
+                    time: self.menu.time(),
+                });
             }
             GameState::Loose => {
                 self.menu.set_loose();
+                if !self.remote {
+                    self.field.explode();
+                }
+                self.events.emit(GameEvent::Lost {
+                    time: self.menu.time(),
+                });
             }
         }
     }
@@ -193,37 +579,263 @@ impl Game {
         args: &ButtonArgs,
         point: Point<f64>,
     ) -> CellInteractionResult {
-        let cell_point = Point {
-            x: (point.x / CELL_SIZE as f64) as u32,
-            y: ((point.y - self.menu.height() as f64) / CELL_SIZE as f64) as u32,
+        let cell_point = self.cell_under(point);
+        let cell_point = match cell_point {
+            Some(cell_point) => cell_point,
+            None => {
+                self.field.release_pressed();
+                return CellInteractionResult::NoAction;
+            }
         };
+        if self.both_buttons_flag && args.state == ButtonState::Press {
+            // both buttons are held
+            self.field.press_chord(cell_point);
+            return CellInteractionResult::NoAction;
+        }
+        if self.both_buttons_flag && self.button_press_counter == 0 {
+            // the button released last finishes the chord without acting on its own
+            self.both_buttons_flag = false;
+            return CellInteractionResult::NoAction;
+        }
+        let chord = self.both_buttons_flag && self.button_press_counter == 1; //this means unpressed last button
+        let command = match self.field.button_action(args, cell_point, chord) {
+            Some(command) => command,
+            None => return CellInteractionResult::NoAction,
+        };
+        if self.remote {
+            self.moves.push(Move::Cell(command, cell_point));
+            return CellInteractionResult::NoAction;
+        }
+        if self.starts_game(command) {
+            self.start(cell_point);
+        }
+        self.perform(command, cell_point)
+    }
 
-        if args.state == ButtonState::Release
-            && args.button == Button::from(MouseButton::Left)
-            && self.game_state == GameState::Ready
-        {
-            self.field.init(cell_point);
-            self.enter_new_state(GameState::Playing);
+    fn perform(&mut self, command: CellCommand, cell: Point<u32>) -> CellInteractionResult {
+        let cells_left = self.field.cells_left();
+        let result = self.field.command(command, cell);
+        self.emit_interaction(&result, cell, cells_left - self.field.cells_left());
+        result
+    }
+
+    // the clock of a race doesn't stop and a shared board can't be hidden
+    fn can_pause(&self) -> bool {
+        !self.race && !self.shared
+    }
+
+    // on a dealt board any move starts the clock, flags can't be placed for free
+    fn starts_game(&self, command: CellCommand) -> bool {
+        self.game_state == GameState::Ready && (command == CellCommand::Open || self.deal.is_some())
+    }
+
+    // mines are placed on the first opened cell, unless the board has been dealt
+    fn start(&mut self, first_cell: Point<u32>) {
+        if self.deal.is_none() {
+            self.field.init(first_cell);
         }
-        if args.state == ButtonState::Press
-            && (args.button == Button::from(MouseButton::Left)
-                || args.button == Button::from(MouseButton::Right))
-        {
-            self.button_press_counter += 1;
-            if self.button_press_counter == 2 {
-                self.both_buttons_flag = true;
+        self.enter_new_state(GameState::Playing);
+        self.events.emit(GameEvent::GameStarted);
+    }
+
+    fn deal(&mut self) {
+        match &self.deal {
+            Some(Deal::Seeded(seed, first_cell)) => {
+                self.field.set_seed(Some(*seed));
+                self.field.init(*first_cell);
+                self.field.open(*first_cell);
+                self.field.set_seed(self.seed);
             }
-        } else if args.state == ButtonState::Release
-            && (args.button == Button::from(MouseButton::Left)
-                || args.button == Button::from(MouseButton::Right))
+            Some(Deal::Layout(layout)) => self.field.lay_out(layout),
+            None => {}
+        }
+    }
+
+    fn emit_interaction(&self, result: &CellInteractionResult, cell: Point<u32>, cells: u32) {
+        let event = match result {
+            CellInteractionResult::NoAction => return,
+            CellInteractionResult::Opened if cells > 1 => GameEvent::CascadeOpened { cell, cells },
+            CellInteractionResult::Opened => GameEvent::CellOpened { cell },
+            CellInteractionResult::Chorded => GameEvent::Chorded { cell, cells },
+            CellInteractionResult::Flagged => GameEvent::Flagged { cell },
+            CellInteractionResult::Unflagged => GameEvent::Unflagged { cell },
+            CellInteractionResult::Exploded => GameEvent::Exploded { cell },
+        };
+        self.events.emit(event);
+    }
+}
+
+/// Moves which can be made without the mouse, used by bots.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellCommand {
+    Open,
+    Flag,
+    Chord,
+}
+
+/// Moves made on a remote board, which are sent to its owner.
+#[derive(Debug, Copy, Clone)]
+pub enum Move {
+    Cell(CellCommand, Point<u32>),
+    NewGame,
+}
+
+/// Board set up before the first move, so every player starts from the same one.
+#[derive(Clone)]
+pub enum Deal {
+    /// Mines given by the seed with the cell already opened, nobody gets a luckier
+    /// first click.
+    Seeded(u64, Point<u32>),
+    Layout(field::Layout),
+}
+
+// logic level access to the game
+impl Game {
+    pub fn state(&self) -> GameState {
+        self.game_state
+    }
+
+    pub fn field(&self) -> &field::Field {
+        &self.field
+    }
+
+    /// Cells changed up to the last update, moves made after it are in the next changes.
+    pub fn changes(&self) -> &field::Changes {
+        &self.changes
+    }
+
+    pub fn new_game(&mut self) {
+        if self.remote {
+            self.moves.push(Move::NewGame);
+            return;
+        }
+        self.switch_state(GameState::Ready);
+    }
+
+    pub fn time(&self) -> f64 {
+        self.menu.time()
+    }
+
+    /// In a race, games can be started only with `start_seeded`, so every player gets the
+    /// same board at the same time. New Game, first clicks and pause do nothing.
+    pub fn set_race(&mut self, race: bool) {
+        self.race = race;
+        if race && self.game_state == GameState::Paused {
+            self.switch_state(GameState::Playing);
+        }
+    }
+
+    /// Starts a new game with the mines given by the seed and the first opened cell.
+    pub fn start_seeded(&mut self, seed: u64, first_cell: Point<u32>) -> CellInteractionResult {
+        self.field.set_seed(Some(seed));
+        self.switch_state(GameState::Ready);
+        let result = self.command(CellCommand::Open, first_cell);
+        // mines are placed by now, later games get the seed from the settings again
+        self.field.set_seed(self.seed);
+        result
+    }
+
+    /// Every new game is dealt before the first move, which starts the clock.
+    pub fn set_deal(&mut self, deal: Option<Deal>) {
+        self.deal = deal;
+        self.switch_state(GameState::Ready);
+    }
+
+    pub fn set_scoreboard(&mut self, lines: Vec<String>) {
+        self.scoreboard = lines;
+    }
+
+    /// A shared board is seen by other players, so it can't be paused.
+    pub fn set_shared(&mut self, shared: bool) {
+        self.shared = shared;
+        if shared && self.game_state == GameState::Paused {
+            self.switch_state(GameState::Playing);
+        }
+    }
+
+    /// The moves on a remote board are only collected, see `take_moves`, and the board
+    /// is shown with `show_board`.
+    pub fn set_remote(&mut self, remote: bool) {
+        self.remote = remote;
+        self.set_shared(remote);
+    }
+
+    pub fn take_moves(&mut self) -> Vec<Move> {
+        std::mem::take(&mut self.moves)
+    }
+
+    /// Shows the state of a remote board.
+    pub fn show_board(&mut self, state: GameState, time: f64, view: &[Vec<CellView>]) {
+        self.show_state(state, time);
+        self.field.set_view(view);
+        self.show_mines_left();
+    }
+
+    pub fn show_state(&mut self, state: GameState, time: f64) {
+        if state != self.game_state {
+            self.switch_state(state);
+        }
+        self.show_time(time);
+    }
+
+    pub fn show_time(&mut self, time: f64) {
+        self.menu.set_time(time);
+    }
+
+    /// Shows the cells of a remote board which have changed.
+    pub fn show_cells(&mut self, cells: &[(Point<u32>, CellView)]) {
+        for &(cell, view) in cells {
+            self.field.set_cell_view(cell, view);
+        }
+        self.show_mines_left();
+    }
+
+    // a game without lives is lost on the first mine
+    fn lives_left(&self) -> u32 {
+        self.lives
+            .map_or(0, |lives| lives.saturating_sub(self.field.hit_cells()))
+    }
+
+    fn show_lives(&mut self) {
+        let lives_left = self.lives_left();
+        self.menu
+            .set_lives(self.lives.map(|lives| (lives_left, lives)));
+    }
+
+    fn show_mines_left(&mut self) {
+        if self.game_state != GameState::Win {
+            self.menu.set_mines(self.field.mines_left());
+        }
+    }
+
+    /// Cell under the mouse cursor.
+    pub fn cursor(&self) -> Option<Point<u32>> {
+        self.mouse_position.and_then(|point| self.cell_under(point))
+    }
+
+    pub fn set_cursors(&mut self, cursors: Vec<(Point<u32>, Color)>) {
+        self.cursors = cursors;
+    }
+
+    /// Applies the move like the matching click would, cells outside of the field and
+    /// moves after the game is over do nothing. Moves on a remote board are only collected.
+    pub fn command(&mut self, command: CellCommand, cell: Point<u32>) -> CellInteractionResult {
+        if !self.field.contains(cell)
+            || (self.game_state != GameState::Ready && self.game_state != GameState::Playing)
         {
-            self.button_press_counter -= 1;
+            return CellInteractionResult::NoAction;
+        }
+        if self.remote {
+            self.moves.push(Move::Cell(command, cell));
+            return CellInteractionResult::NoAction;
+        }
+        if self.starts_game(command) {
+            self.start(cell);
         }
-        self.field.button_action(
-            args,
-            cell_point,
-            self.both_buttons_flag && self.button_press_counter == 1,
-        ) //this means unpressed last button
+        let result = self.perform(command, cell);
+        self.apply_result(&result);
+        result
     }
 }
 
@@ -231,3 +843,96 @@ pub trait GameElement {
     fn render(&self, render_args: &RenderArgs, c: Context, gl: &mut GlGraphics, dd: &mut DrawData);
     fn update(&mut self, update_args: &UpdateArgs);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(args: &[&str]) -> Game {
+        let args = args.iter().map(|arg| arg.to_string());
+        let mut settings = Settings::from_args(args).unwrap();
+        settings.animations = false;
+        Game::new(settings)
+    }
+
+    fn move_to_cell(game: &mut Game, cell: Point<u32>) {
+        let center = game.field.geometry().cell_center(cell);
+        let origin = game.camera.origin();
+        game.mouse_move(&[origin.x + center.x, origin.y + center.y]);
+    }
+
+    fn left(game: &mut Game, state: ButtonState) {
+        game.button_action(&ButtonArgs {
+            state,
+            button: Button::from(MouseButton::Left),
+            scancode: None,
+        });
+    }
+
+    #[test]
+    fn press_released_over_the_menu_is_forgotten() {
+        let mut game = game(&[]);
+        let cell = Point { x: 3, y: 3 };
+        move_to_cell(&mut game, cell);
+        left(&mut game, ButtonState::Press);
+        game.mouse_move(&[1.0, 1.0]);
+        left(&mut game, ButtonState::Release);
+        assert_eq!(game.state(), GameState::Ready);
+        move_to_cell(&mut game, cell);
+        left(&mut game, ButtonState::Press);
+        left(&mut game, ButtonState::Release);
+        assert_eq!(game.state(), GameState::Playing);
+        assert!(game.field.cell(cell).state() == cell::CellState::Opened);
+    }
+
+    fn dealt(args: &[&str], mines: &[(u32, u32)], opened: &[(u32, u32)]) -> Game {
+        let points = |cells: &[(u32, u32)]| cells.iter().map(|&(x, y)| Point { x, y }).collect();
+        let mut game = game(args);
+        game.set_deal(Some(Deal::Layout(field::Layout {
+            mines: points(mines),
+            opened: points(opened),
+            flagged: Vec::new(),
+        })));
+        game
+    }
+
+    #[test]
+    fn game_is_lost_on_the_last_life() {
+        let mines = [(0, 0), (3, 0), (0, 3)];
+        let mut game = dealt(&["--size", "4x4x3", "--lives", "3"], &mines, &[]);
+        for (hit, &(x, y)) in mines.iter().enumerate() {
+            assert_ne!(game.state(), GameState::Loose);
+            let result = game.command(CellCommand::Open, Point { x, y });
+            assert!(matches!(result, CellInteractionResult::Exploded));
+            assert_eq!(game.lives_left(), 2 - hit as u32);
+        }
+        assert_eq!(game.state(), GameState::Loose);
+    }
+
+    #[test]
+    fn survived_hit_counts_as_a_found_mine() {
+        let mut game = dealt(&["--size", "4x4x2", "--lives", "2"], &[(0, 0), (3, 3)], &[]);
+        game.command(CellCommand::Open, Point { x: 0, y: 0 });
+        assert_eq!(game.state(), GameState::Playing);
+        assert_eq!(game.lives_left(), 1);
+        assert_eq!(game.field.mines_left(), 1);
+        game.command(CellCommand::Flag, Point { x: 3, y: 3 });
+        assert_eq!(game.field.mines_left(), 0);
+        assert_eq!(game.lives_left(), 1);
+    }
+
+    #[test]
+    fn chord_which_hits_a_mine_and_clears_the_board_wins() {
+        // the doubly flagged corner makes the chord on the 2 open the other mine
+        let opened = [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)];
+        let args = ["--size", "3x3x2", "--lives", "2", "--mines-per-cell", "2"];
+        let mut game = dealt(&args, &[(0, 0), (2, 2)], &opened);
+        game.command(CellCommand::Flag, Point { x: 0, y: 0 });
+        game.command(CellCommand::Flag, Point { x: 0, y: 0 });
+        let result = game.command(CellCommand::Chord, Point { x: 1, y: 1 });
+        assert!(matches!(result, CellInteractionResult::Exploded));
+        assert_eq!(game.field.cells_left(), 0);
+        assert_eq!(game.lives_left(), 1);
+        assert_eq!(game.state(), GameState::Win);
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::game::cell::CellView;
+use crate::game::field::Field;
+use crate::game::geometry::{GridKind, Kernel};
+use crate::game::settings::Settings;
+use crate::game::{FieldSize, Point};
+
+/// Everything a player needs to build the same board as the host.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BoardSpec {
+    #[serde(flatten)]
+    size: FieldSize,
+    grid: GridKind,
+    kernel: Kernel,
+    mines_per_cell: u8,
+}
+
+impl BoardSpec {
+    pub fn new(settings: &Settings) -> BoardSpec {
+        BoardSpec {
+            size: settings.field_size,
+            grid: settings.grid,
+            kernel: settings.kernel.clone(),
+            mines_per_cell: settings.mines_per_cell,
+        }
+    }
+
+    pub fn apply(self, settings: &mut Settings) {
+        settings.field_size = self.size;
+        settings.grid = self.grid;
+        settings.kernel = self.kernel;
+        settings.mines_per_cell = self.mines_per_cell;
+    }
+}
+
+/// Board as rows of cell tokens, see `CellView::token`.
+pub fn tokens(view: &[Vec<CellView>]) -> Vec<Vec<String>> {
+    view.iter()
+        .map(|row| row.iter().map(|cell| cell.token()).collect())
+        .collect()
+}
+
+pub fn parse_tokens(cells: &[Vec<String>]) -> Option<Vec<Vec<CellView>>> {
+    cells
+        .iter()
+        .map(|row| row.iter().map(|token| CellView::parse(token)).collect())
+        .collect()
+}
+
+/// One cell of a board which has changed, as a token like in `tokens`.
+#[derive(Serialize, Deserialize)]
+pub struct CellChange {
+    x: u32,
+    y: u32,
+    cell: String,
+}
+
+/// The cells as they are now on the field.
+pub fn cell_changes(field: &Field, cells: &[Point<u32>]) -> Vec<CellChange> {
+    cells
+        .iter()
+        .map(|&point| CellChange {
+            x: point.x,
+            y: point.y,
+            cell: field.cell(point).view().token(),
+        })
+        .collect()
+}
+
+/// Changes with a cell which can't be read are dropped.
+pub fn parse_cell_changes(changes: &[CellChange]) -> Vec<(Point<u32>, CellView)> {
+    changes
+        .iter()
+        .filter_map(|change| {
+            let point = Point {
+                x: change.x,
+                y: change.y,
+            };
+            Some((point, CellView::parse(&change.cell)?))
+        })
+        .collect()
+}
+
+/// Name shown to the other players, the user name unless one is given.
+pub fn player_name(settings: &Settings) -> String {
+    settings
+        .name
+        .clone()
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| String::from("player"))
+}
+
+/// Listens on every interface without blocking the game loop.
+pub fn listen(port: u16) -> Result<TcpListener, String> {
+    TcpListener::bind(("0.0.0.0", port))
+        .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+        .map_err(|e| format!("Can't listen on port {}: {}", port, e))
+}
+
+/// Connections which have arrived since the last call.
+pub fn accept(listener: &TcpListener) -> Vec<Connection> {
+    let mut connections = Vec::new();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => match Connection::new(stream) {
+                Ok(connection) => connections.push(connection),
+                Err(e) => eprintln!("{}", e),
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) => {
+                eprintln!("Can't accept a connection: {}", e);
+                break;
+            }
+        }
+    }
+    connections
+}
+
+/// TCP connection exchanging one JSON message per line. Reading blocks, so lines are
+/// passed to the game loop through a channel like the bot's output.
+pub struct Connection {
+    stream: TcpStream,
+    lines: Receiver<String>,
+    closed: bool,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Connection, String> {
+        // accepted streams may inherit the non-blocking mode of the listener
+        stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_nodelay(true))
+            .map_err(|e| format!("Can't set up connection: {}", e))?;
+        let reader = stream
+            .try_clone()
+            .map_err(|e| format!("Can't set up connection: {}", e))?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Connection {
+            stream,
+            lines,
+            closed: false,
+        })
+    }
+
+    pub fn connect(address: &str) -> Result<Connection, String> {
+        let stream = TcpStream::connect(address)
+            .map_err(|e| format!("Can't connect to {}: {}", address, e))?;
+        Connection::new(stream)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn peer(&self) -> String {
+        self.stream
+            .peer_addr()
+            .map_or_else(|_| String::from("unknown"), |address| address.to_string())
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) {
+        if self.closed {
+            return;
+        }
+        let line = serde_json::to_string(message).expect("Message should be serialized");
+        if writeln!(self.stream, "{}", line).is_err() {
+            self.close();
+        }
+    }
+
+    /// Next message if one has arrived, lines which can't be parsed are returned as errors.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Option<Result<T, String>> {
+        match self.lines.try_recv() {
+            Ok(line) => Some(parse(&line)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.close();
+                None
+            }
+        }
+    }
+
+    /// Waits for the next message, used only while a connection is set up.
+    pub fn receive_blocking<T: DeserializeOwned>(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<T, String> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => parse(&line),
+            Err(RecvTimeoutError::Timeout) => Err(format!("{} hasn't answered", self.peer())),
+            Err(RecvTimeoutError::Disconnected) => {
+                let peer = self.peer();
+                self.close();
+                Err(format!("{} has closed the connection", peer))
+            }
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// the reader thread holds a clone of the stream, which would keep the connection open
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+fn parse<T: DeserializeOwned>(line: &str) -> Result<T, String> {
+    serde_json::from_str(line).map_err(|e| format!("Can't parse message {}: {}", line, e))
+}
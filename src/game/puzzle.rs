@@ -0,0 +1,710 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::assets::{self, Assets};
+use crate::game::cell::CellState;
+use crate::game::field::Layout;
+use crate::game::generator::Guesses;
+use crate::game::geometry::{Geometry, GridKind, Kernel};
+use crate::game::settings::Settings;
+use crate::game::{Deal, FieldSize, Game, GameState, Point};
+
+const SOLVED_FILE: &str = "puzzles.json";
+
+/// Board with some cells opened or flagged from the start, meant to be solved without
+/// guessing.
+pub struct Puzzle {
+    pub title: String,
+    pub difficulty: String,
+    pub author: String,
+    grid: GridKind,
+    kernel: Kernel,
+    size: FieldSize,
+    layout: Layout,
+}
+
+impl Puzzle {
+    pub fn new(
+        title: String,
+        difficulty: String,
+        author: String,
+        grid: GridKind,
+        kernel: Kernel,
+        size: FieldSize,
+        layout: Layout,
+    ) -> Puzzle {
+        Puzzle {
+            title,
+            difficulty,
+            author,
+            grid,
+            kernel,
+            size,
+            layout,
+        }
+    }
+
+    /// The puzzle in the format of a pack file, starting with its `[puzzle]` line.
+    pub fn write(&self) -> String {
+        let mut text = format!(
+            "[puzzle]\ntitle = {}\ndifficulty = {}\nauthor = {}\n",
+            self.title, self.difficulty, self.author
+        );
+        if self.grid != GridKind::Square {
+            text.push_str(&format!("grid = {}\n", self.grid.name()));
+        }
+        if self.kernel != Kernel::default() {
+            text.push_str(&format!("kernel = {}\n", self.kernel.spec()));
+        }
+        let mut rows = vec![vec!['.'; self.size.width as usize]; self.size.height as usize];
+        let mut mark = |cells: &[Point<u32>], mark: char| {
+            for cell in cells {
+                rows[cell.y as usize][cell.x as usize] = mark;
+            }
+        };
+        mark(&self.layout.opened, 'o');
+        mark(&self.layout.mines, '*');
+        mark(&self.layout.flagged, 'F');
+        for row in rows {
+            let row: Vec<String> = row.iter().map(char::to_string).collect();
+            text.push_str(&row.join(" "));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+pub struct PuzzlePack {
+    pub name: String,
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl PuzzlePack {
+    /// Pack file is a list of `key = value` lines like a theme file: the `name` and the
+    /// `author` of the pack, then every puzzle starts with a `[puzzle]` line followed by
+    /// its `title`, `difficulty`, `author`, `grid` and `kernel` and the rows of its board.
+    /// In the rows `*` is a mine, `F` a flagged mine, `o` an opened cell and `.` a closed one.
+    /// Solved puzzles are remembered by title, so no two puzzles of a pack share one.
+    pub fn load(path: &Path) -> Result<PuzzlePack, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read puzzles {}: {}", path.display(), e))?;
+        PuzzlePack::parse(path, &content)
+    }
+
+    // the path names the pack and the errors
+    fn parse(path: &Path, content: &str) -> Result<PuzzlePack, String> {
+        let mut pack = PuzzlePack {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            puzzles: Vec::new(),
+        };
+        let mut author = String::from("unknown");
+        let mut draft: Option<Draft> = None;
+        let at =
+            |line_number: usize, e: String| format!("{}:{}: {}", path.display(), line_number, e);
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[puzzle]" {
+                if let Some(draft) = draft.take() {
+                    let line_number = draft.line_number;
+                    pack.add(draft).map_err(|e| at(line_number, e))?;
+                }
+                draft = Some(Draft::new(line_number + 1, &author));
+                continue;
+            }
+            let added = match &mut draft {
+                Some(draft) => draft.add(line),
+                None => pack.set(line, &mut author),
+            };
+            added.map_err(|e| at(line_number + 1, e))?;
+        }
+        if let Some(draft) = draft {
+            let line_number = draft.line_number;
+            pack.add(draft).map_err(|e| at(line_number, e))?;
+        }
+        if pack.puzzles.is_empty() {
+            return Err(format!("{} has no puzzles", path.display()));
+        }
+        Ok(pack)
+    }
+
+    fn add(&mut self, draft: Draft) -> Result<(), String> {
+        let puzzle = draft.finish()?;
+        if self.puzzles.iter().any(|other| other.title == puzzle.title) {
+            return Err(format!("Another puzzle is already called {}", puzzle.title));
+        }
+        self.puzzles.push(puzzle);
+        Ok(())
+    }
+
+    fn set(&mut self, line: &str, author: &mut String) -> Result<(), String> {
+        match key_value(line)? {
+            ("name", value) => self.name = String::from(value),
+            ("author", value) => *author = String::from(value),
+            (key, _) => return Err(format!("Unknown key {}", key)),
+        }
+        Ok(())
+    }
+}
+
+// puzzle being read from a pack
+struct Draft {
+    // of the `[puzzle]` line, errors in the board are reported there
+    line_number: usize,
+    title: Option<String>,
+    difficulty: String,
+    author: String,
+    grid: GridKind,
+    kernel: Kernel,
+    rows: Vec<Vec<char>>,
+}
+
+impl Draft {
+    fn new(line_number: usize, author: &str) -> Draft {
+        Draft {
+            line_number,
+            title: None,
+            difficulty: String::from("unrated"),
+            author: String::from(author),
+            grid: GridKind::Square,
+            kernel: Kernel::default(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, line: &str) -> Result<(), String> {
+        if !line.contains('=') {
+            let row: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+            if let Some(unknown) = row.iter().find(|c| !"*Fo.".contains(**c)) {
+                return Err(format!("Unknown cell {}, expected * F o or .", unknown));
+            }
+            self.rows.push(row);
+            return Ok(());
+        }
+        match key_value(line)? {
+            ("title", value) => self.title = Some(String::from(value)),
+            ("difficulty", value) => self.difficulty = String::from(value),
+            ("author", value) => self.author = String::from(value),
+            ("grid", value) => {
+                self.grid = match value {
+                    "square" => GridKind::Square,
+                    "hex" => GridKind::Hexagonal,
+                    "tri" => GridKind::Triangular,
+                    other => return Err(format!("Unknown grid {}", other)),
+                }
+            }
+            ("kernel", value) => self.kernel = Kernel::parse(value)?,
+            (key, _) => return Err(format!("Unknown key {}", key)),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Puzzle, String> {
+        let title = self.title.ok_or("Puzzle has no title")?;
+        if self.grid != GridKind::Square && self.kernel != Kernel::default() {
+            return Err(format!(
+                "Kernel of {} is supported on square grid only",
+                title
+            ));
+        }
+        let width = self.rows.first().map_or(0, Vec::len);
+        if self.rows.iter().any(|row| row.len() != width) {
+            return Err(format!("Rows of {} have different lengths", title));
+        }
+        if !(2..=1000).contains(&width) || !(2..=1000).contains(&self.rows.len()) {
+            return Err(format!(
+                "Board of {} should be 2-1000 cells each way",
+                title
+            ));
+        }
+        let mut layout = Layout {
+            mines: Vec::new(),
+            opened: Vec::new(),
+            flagged: Vec::new(),
+        };
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let point = Point {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                match cell {
+                    '*' => layout.mines.push(point),
+                    'F' => {
+                        layout.mines.push(point);
+                        layout.flagged.push(point);
+                    }
+                    'o' => layout.opened.push(point),
+                    _ => {}
+                }
+            }
+        }
+        if layout.mines.is_empty() {
+            return Err(format!("{} has no mines", title));
+        }
+        let size = FieldSize {
+            width: width as u32,
+            height: self.rows.len() as u32,
+            mines: layout.mines.len() as u32,
+        };
+        // empty opened cells open their neighbours, which can leave nothing closed
+        let geometry = Geometry::new(self.grid, self.kernel.clone());
+        let mut closed = self.rows.clone();
+        let mut cells = layout.opened.clone();
+        while let Some(cell) = cells.pop() {
+            closed[cell.y as usize][cell.x as usize] = 'o';
+            let neighbours = geometry.neighbours(cell, &size);
+            if !neighbours
+                .iter()
+                .any(|around| "*F".contains(self.rows[around.y as usize][around.x as usize]))
+            {
+                cells.extend(
+                    neighbours
+                        .into_iter()
+                        .filter(|around| closed[around.y as usize][around.x as usize] == '.'),
+                );
+            }
+        }
+        if !closed.iter().flatten().any(|&cell| cell == '.') {
+            return Err(format!("{} has no closed cells to solve", title));
+        }
+        Ok(Puzzle {
+            size,
+            title,
+            difficulty: self.difficulty,
+            author: self.author,
+            grid: self.grid,
+            kernel: self.kernel,
+            layout,
+        })
+    }
+}
+
+fn key_value(line: &str) -> Result<(&str, &str), String> {
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| format!("Expected key = value, got {}", line))?;
+    Ok((key.trim(), value.trim()))
+}
+
+/// Puzzle solved at least once, kept in `puzzles.json` in the data directory.
+#[derive(Clone, Serialize, Deserialize)]
+struct Solved {
+    pack: String,
+    title: String,
+    // the best time
+    time: f64,
+}
+
+/// Plays one puzzle of a pack, the first unsolved one unless another is picked. A puzzle
+/// finished with a guess isn't counted as solved.
+pub struct Puzzles {
+    pack: PuzzlePack,
+    // index of the puzzle being played
+    index: usize,
+    path: Option<PathBuf>,
+    solved: Vec<Solved>,
+    state: GameState,
+    guesses: Guesses,
+    guessed: bool,
+}
+
+impl Puzzles {
+    /// Loads the pack and replaces the board settings with the ones of the puzzle.
+    /// Puzzles are numbered from 1.
+    pub fn load(
+        spec: &str,
+        number: Option<usize>,
+        assets: &Assets,
+        settings: &mut Settings,
+    ) -> Result<Puzzles, String> {
+        let pack = PuzzlePack::load(&assets.require(spec)?)?;
+        let path = assets::data_dir().map(|dir| dir.join(SOLVED_FILE));
+        let solved = load(path.as_ref());
+        let count = pack.puzzles.len();
+        let index = match number {
+            Some(number @ 1..) if number <= count => number - 1,
+            Some(_) => return Err(format!("The pack has puzzles 1-{}", count)),
+            None => next_unsolved(&pack, &solved, 0).unwrap_or_else(|| {
+                eprintln!("Every puzzle of the pack is solved");
+                0
+            }),
+        };
+        let puzzles = Puzzles::new(pack, index, path, solved);
+        puzzles.list();
+        let puzzle = puzzles.puzzle();
+        settings.field_size = puzzle.size;
+        settings.grid = puzzle.grid;
+        settings.kernel = puzzle.kernel.clone();
+        settings.mines_per_cell = 1;
+        Ok(puzzles)
+    }
+
+    fn new(pack: PuzzlePack, index: usize, path: Option<PathBuf>, solved: Vec<Solved>) -> Puzzles {
+        let puzzle = &pack.puzzles[index];
+        let geometry = Geometry::new(puzzle.grid, puzzle.kernel.clone());
+        let guesses = Guesses::new(puzzle.size, &geometry, &puzzle.layout);
+        Puzzles {
+            pack,
+            index,
+            path,
+            solved,
+            state: GameState::Ready,
+            guesses,
+            guessed: false,
+        }
+    }
+
+    pub fn deal(&self, game: &mut Game) {
+        game.set_deal(Some(Deal::Layout(self.puzzle().layout.clone())));
+        game.set_scoreboard(self.scoreboard());
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        let state = game.state();
+        if state != GameState::Ready {
+            self.check_guesses(game);
+        }
+        if state == self.state {
+            return;
+        }
+        self.state = state;
+        if state == GameState::Ready {
+            self.guesses.reset();
+            self.set_guessed(false, game);
+        }
+        if state != GameState::Win {
+            return;
+        }
+        let time = game.time();
+        if self.guessed {
+            eprintln!(
+                "Finished {} with a guess, it counts once solved without one",
+                self.puzzle().title
+            );
+            return;
+        }
+        eprintln!("Solved {} in {:.1}s", self.puzzle().title, time);
+        let (pack, title) = (self.pack.name.clone(), self.puzzle().title.clone());
+        match self
+            .solved
+            .iter_mut()
+            .find(|solved| solved.pack == pack && solved.title == title)
+        {
+            Some(solved) => solved.time = solved.time.min(time),
+            None => self.solved.push(Solved { pack, title, time }),
+        }
+        self.save();
+        if let Some(next) = self.next_unsolved(self.index + 1) {
+            eprintln!(
+                "Next unsolved puzzle: {}, play it with --puzzle {}",
+                self.pack.puzzles[next].title,
+                next + 1
+            );
+        }
+        game.set_scoreboard(self.scoreboard());
+    }
+
+    // the cells opened since the last update are checked against the ones opened before
+    fn check_guesses(&mut self, game: &mut Game) {
+        let field = game.field();
+        let changes = game.changes();
+        let cells: Vec<Point<u32>> = match changes.cells() {
+            Some(cells) => cells.to_vec(),
+            None => {
+                // the whole board has changed, like after a new game, so every opened
+                // cell is checked from the start, which finds the same guesses as
+                // checking them move by move
+                self.guesses.reset();
+                let size = field.size();
+                (0..size.height)
+                    .flat_map(|y| (0..size.width).map(move |x| Point { x, y }))
+                    .collect()
+            }
+        };
+        let opened: Vec<Point<u32>> = cells
+            .into_iter()
+            .filter(|&cell| field.cell(cell).state() == CellState::Opened)
+            .collect();
+        let guess = self.guesses.open(&opened);
+        if let (Some(guess), false) = (guess, self.guessed) {
+            eprintln!(
+                "Opening {},{} was a guess, the puzzle won't count as solved",
+                guess.x, guess.y
+            );
+        }
+        if guess.is_some() || changes.cells().is_none() {
+            self.set_guessed(guess.is_some(), game);
+        }
+    }
+
+    fn set_guessed(&mut self, guessed: bool, game: &mut Game) {
+        if guessed != self.guessed {
+            self.guessed = guessed;
+            game.set_scoreboard(self.scoreboard());
+        }
+    }
+
+    fn puzzle(&self) -> &Puzzle {
+        &self.pack.puzzles[self.index]
+    }
+
+    fn best_time(&self, puzzle: &Puzzle) -> Option<f64> {
+        self.solved
+            .iter()
+            .find(|solved| solved.pack == self.pack.name && solved.title == puzzle.title)
+            .map(|solved| solved.time)
+    }
+
+    fn next_unsolved(&self, from: usize) -> Option<usize> {
+        next_unsolved(&self.pack, &self.solved, from)
+    }
+
+    fn list(&self) {
+        eprintln!("Puzzles of {}:", self.pack.name);
+        for (index, puzzle) in self.pack.puzzles.iter().enumerate() {
+            let solved = self
+                .best_time(puzzle)
+                .map(|time| format!(", solved in {:.1}s", time))
+                .unwrap_or_default();
+            let marker = if index == self.index { ">" } else { " " };
+            eprintln!(
+                "{} {}. {} ({}, by {}{})",
+                marker,
+                index + 1,
+                puzzle.title,
+                puzzle.difficulty,
+                puzzle.author,
+                solved
+            );
+        }
+    }
+
+    fn scoreboard(&self) -> Vec<String> {
+        let puzzle = self.puzzle();
+        let status = match self.best_time(puzzle) {
+            _ if self.guessed => String::from("Guessed, won't count as solved"),
+            Some(time) => format!("Solved, best {:.1}s", time),
+            None => String::from("Not solved yet"),
+        };
+        vec![
+            format!(
+                "{}/{} {}",
+                self.index + 1,
+                self.pack.puzzles.len(),
+                puzzle.title
+            ),
+            format!("{}, by {}", puzzle.difficulty, puzzle.author),
+            status,
+        ]
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let json = serde_json::to_string_pretty(&self.solved)
+            .expect("Solved puzzles should be serialized");
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, json));
+        if let Err(e) = saved {
+            eprintln!("Can't save solved puzzles to {}: {}", path.display(), e);
+        }
+    }
+}
+
+// looks from the given index to the end, then from the start
+fn next_unsolved(pack: &PuzzlePack, solved: &[Solved], from: usize) -> Option<usize> {
+    let count = pack.puzzles.len();
+    (from..count).chain(0..from.min(count)).find(|&index| {
+        let title = &pack.puzzles[index].title;
+        !solved
+            .iter()
+            .any(|solved| solved.pack == pack.name && solved.title == *title)
+    })
+}
+
+fn load(path: Option<&PathBuf>) -> Vec<Solved> {
+    let Some(json) = path.and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("Can't read solved puzzles: {}", e);
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CellCommand, GameElement};
+    use piston::UpdateArgs;
+
+    fn parse(content: &str) -> Result<PuzzlePack, String> {
+        PuzzlePack::parse(Path::new("test.puzzles"), content)
+    }
+
+    fn play(content: &str) -> (Puzzles, Game) {
+        let puzzles = Puzzles::new(parse(content).unwrap(), 0, None, Vec::new());
+        let puzzle = puzzles.puzzle();
+        let mut game = Game::new(Settings {
+            field_size: puzzle.size,
+            grid: puzzle.grid,
+            kernel: puzzle.kernel.clone(),
+            mines_per_cell: 1,
+            animations: false,
+            ..Settings::default()
+        });
+        puzzles.deal(&mut game);
+        (puzzles, game)
+    }
+
+    fn step(puzzles: &mut Puzzles, game: &mut Game) {
+        game.update(&UpdateArgs { dt: 0.0 });
+        puzzles.update(game);
+    }
+
+    #[test]
+    fn pack_is_read() {
+        let pack = parse(
+            "name = Basics\n\
+             author = Ann\n\
+             # comments and empty lines are skipped\n\
+             \n\
+             [puzzle]\n\
+             title = First\n\
+             difficulty = easy\n\
+             o o o\n\
+             o * .\n\
+             [puzzle]\n\
+             title = Second\n\
+             author = Bob\n\
+             grid = hex\n\
+             F . .\n\
+             o . .\n",
+        )
+        .unwrap();
+        assert_eq!(pack.name, "Basics");
+        assert_eq!(pack.puzzles.len(), 2);
+        let first = &pack.puzzles[0];
+        assert_eq!(
+            (first.title.as_str(), first.difficulty.as_str()),
+            ("First", "easy")
+        );
+        assert_eq!(first.author, "Ann");
+        assert_eq!(first.grid, GridKind::Square);
+        assert_eq!(first.layout.mines, vec![Point { x: 1, y: 1 }]);
+        assert_eq!(first.layout.opened.len(), 4);
+        let second = &pack.puzzles[1];
+        assert_eq!(second.author, "Bob");
+        assert_eq!(second.difficulty, "unrated");
+        assert_eq!(second.grid, GridKind::Hexagonal);
+        assert_eq!(second.layout.flagged, vec![Point { x: 0, y: 0 }]);
+    }
+
+    #[test]
+    fn written_puzzle_is_read_back() {
+        let pack = parse("[puzzle]\ntitle = Round trip\n. * .\no o F\n").unwrap();
+        let text = pack.puzzles[0].write();
+        let puzzle = &parse(&text).unwrap().puzzles[0];
+        assert_eq!(puzzle.write(), text);
+    }
+
+    #[test]
+    fn kernel_is_kept_with_the_puzzle() {
+        let text = "[puzzle]\ntitle = Knight\nkernel = knight\n* . .\no o o\n";
+        let puzzle = &parse(text).unwrap().puzzles[0];
+        assert_eq!(puzzle.kernel, Kernel::parse("knight").unwrap());
+        assert!(puzzle.write().contains("kernel = knight\n"));
+        let e = parse("[puzzle]\ntitle = Hex\ngrid = hex\nkernel = knight\n. *\no o\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e,
+            "test.puzzles:1: Kernel of Hex is supported on square grid only"
+        );
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let e = parse("[puzzle]\ntitle = Bad\no o\no x\n").err().unwrap();
+        assert_eq!(e, "test.puzzles:4: Unknown cell x, expected * F o or .");
+        let e = parse("name = Pack\ncolour = red\n").err().unwrap();
+        assert_eq!(e, "test.puzzles:2: Unknown key colour");
+    }
+
+    #[test]
+    fn board_errors_point_at_the_puzzle() {
+        let e = parse("[puzzle]\ntitle = Ok\no *\n. o\n\n[puzzle]\ntitle = Ragged\no *\n.\n")
+            .err()
+            .unwrap();
+        assert_eq!(e, "test.puzzles:6: Rows of Ragged have different lengths");
+    }
+
+    #[test]
+    fn puzzle_needs_a_title() {
+        let e = parse("[puzzle]\no *\n. o\n").err().unwrap();
+        assert_eq!(e, "test.puzzles:1: Puzzle has no title");
+    }
+
+    #[test]
+    fn puzzle_needs_closed_cells_and_mines() {
+        let e = parse("[puzzle]\ntitle = Open\no *\no o\n").err().unwrap();
+        assert_eq!(e, "test.puzzles:1: Open has no closed cells to solve");
+        // the empty corner opens every closed cell
+        let e = parse("[puzzle]\ntitle = Cascade\no . .\n. . .\n. . *\n")
+            .err()
+            .unwrap();
+        assert_eq!(e, "test.puzzles:1: Cascade has no closed cells to solve");
+        let e = parse("[puzzle]\ntitle = Safe\no .\no o\n").err().unwrap();
+        assert_eq!(e, "test.puzzles:1: Safe has no mines");
+    }
+
+    #[test]
+    fn titles_are_unique() {
+        let e = parse("[puzzle]\ntitle = Twin\no *\n. o\n[puzzle]\ntitle = Twin\n* o\no .\n")
+            .err()
+            .unwrap();
+        assert_eq!(e, "test.puzzles:5: Another puzzle is already called Twin");
+    }
+
+    #[test]
+    fn puzzle_finished_with_a_guess_is_not_solved() {
+        let (mut puzzles, mut game) = play("[puzzle]\ntitle = Coin\n* .\no o\n");
+        step(&mut puzzles, &mut game);
+        game.command(CellCommand::Open, Point { x: 1, y: 0 });
+        step(&mut puzzles, &mut game);
+        assert_eq!(game.state(), GameState::Win);
+        assert!(puzzles.guessed);
+        assert!(puzzles.solved.is_empty());
+        game.new_game();
+        step(&mut puzzles, &mut game);
+        assert!(!puzzles.guessed);
+    }
+
+    #[test]
+    fn deduced_cells_are_no_guesses() {
+        let (mut puzzles, mut game) = play("[puzzle]\ntitle = Told\nF o\n. o\n");
+        step(&mut puzzles, &mut game);
+        game.command(CellCommand::Open, Point { x: 0, y: 1 });
+        step(&mut puzzles, &mut game);
+        assert_eq!(game.state(), GameState::Win);
+        assert!(!puzzles.guessed);
+        assert_eq!(puzzles.solved.len(), 1);
+    }
+
+    #[test]
+    fn pack_needs_puzzles() {
+        let e = parse("name = Empty\n").err().unwrap();
+        assert_eq!(e, "test.puzzles has no puzzles");
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::net::{self, player_name, BoardSpec, Connection};
+use crate::game::settings::Settings;
+use crate::game::{Game, GameState, Point};
+
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RunState {
+    Waiting,
+    Racing,
+    Won,
+    Lost,
+    Left,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Progress {
+    state: RunState,
+    // percent of the safe cells opened
+    cleared: u32,
+    // whole seconds while racing, so progress is sent about once a second
+    time: f64,
+}
+
+impl Progress {
+    const WAITING: Progress = Progress {
+        state: RunState::Waiting,
+        cleared: 0,
+        time: 0.0,
+    };
+
+    fn of(game: &Game) -> Progress {
+        let cleared = (game.field().cleared() * 100.0).floor() as u32;
+        match game.state() {
+            GameState::Ready => Progress::WAITING,
+            GameState::Playing | GameState::Paused => Progress {
+                state: RunState::Racing,
+                cleared,
+                time: game.time().floor(),
+            },
+            GameState::Win => Progress {
+                state: RunState::Won,
+                cleared: 100,
+                time: game.time(),
+            },
+            GameState::Loose => Progress {
+                state: RunState::Lost,
+                cleared,
+                time: game.time(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Standing {
+    name: String,
+    #[serde(flatten)]
+    progress: Progress,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RaceMessage {
+    Join { name: String },
+    Welcome { board: BoardSpec },
+    Start { seed: u64, x: u32, y: u32 },
+    Progress(Progress),
+    Standings { players: Vec<Standing> },
+}
+
+struct Guest {
+    connection: Connection,
+    // known once the guest has introduced itself
+    name: Option<String>,
+    progress: Progress,
+}
+
+enum Role {
+    Host {
+        listener: TcpListener,
+        board: BoardSpec,
+        guests: Vec<Guest>,
+    },
+    Guest {
+        host: Connection,
+    },
+}
+
+/// Several players racing on the same seeded board. The host accepts players and starts
+/// the races, everybody reports their progress to the host, which sends the standings
+/// back to everybody.
+pub struct Race {
+    role: Role,
+    name: String,
+    progress: Progress,
+    standings: Vec<Standing>,
+    finished: bool,
+}
+
+impl Race {
+    pub fn host(port: u16, settings: &Settings) -> Result<Race, String> {
+        let listener = net::listen(port)?;
+        eprintln!(
+            "Waiting for players on port {}, press Enter to start a race",
+            port
+        );
+        Ok(Race::new(
+            Role::Host {
+                listener,
+                board: BoardSpec::new(settings),
+                guests: Vec::new(),
+            },
+            settings,
+        ))
+    }
+
+    /// Connects to the host and replaces the board settings with the ones of the race.
+    pub fn join(address: &str, settings: &mut Settings) -> Result<Race, String> {
+        let mut host = Connection::connect(address)?;
+        host.send(&RaceMessage::Join {
+            name: player_name(settings),
+        });
+        match host.receive_blocking(JOIN_TIMEOUT)? {
+            RaceMessage::Welcome { board } => board.apply(settings),
+            _ => return Err(format!("{} is not hosting a race", address)),
+        }
+        settings
+            .check_board()
+            .map_err(|e| format!("{} has sent a board which can't be played: {}", address, e))?;
+        eprintln!("Joined the race, waiting for the host to start it");
+        Ok(Race::new(Role::Guest { host }, settings))
+    }
+
+    fn new(role: Role, settings: &Settings) -> Race {
+        Race {
+            role,
+            name: player_name(settings),
+            progress: Progress::WAITING,
+            standings: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Starts a race on a new board for everybody, only the host can do it.
+    pub fn start(&mut self, game: &mut Game) {
+        let Role::Host { guests, .. } = &mut self.role else {
+            return;
+        };
+        let seed = rand::random();
+        let size = game.field().size();
+        let x = size.width / 2;
+        let y = size.height / 2;
+        guests.retain(|guest| !guest.connection.is_closed());
+        for guest in guests.iter_mut().filter(|guest| guest.name.is_some()) {
+            guest.connection.send(&RaceMessage::Start { seed, x, y });
+            guest.progress = Progress::WAITING;
+        }
+        game.start_seeded(seed, Point { x, y });
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        if self.finished {
+            return;
+        }
+        let progress = Progress::of(game);
+        let changed = progress != self.progress;
+        self.progress = progress;
+        match self.role {
+            Role::Host { .. } => self.update_host(changed),
+            Role::Guest { .. } => self.update_guest(game, changed),
+        }
+        game.set_scoreboard(self.scoreboard());
+    }
+
+    fn update_host(&mut self, mut changed: bool) {
+        let Role::Host {
+            listener,
+            board,
+            guests,
+        } = &mut self.role
+        else {
+            return;
+        };
+        guests.extend(net::accept(listener).into_iter().map(|connection| Guest {
+            connection,
+            name: None,
+            progress: Progress::WAITING,
+        }));
+        for i in 0..guests.len() {
+            while let Some(message) = guests[i].connection.receive() {
+                match message {
+                    Ok(RaceMessage::Join { name }) => {
+                        let name = unique_name(&name, &self.name, guests);
+                        eprintln!("{} has joined the race", name);
+                        let guest = &mut guests[i];
+                        guest.name = Some(name);
+                        guest.connection.send(&RaceMessage::Welcome {
+                            board: board.clone(),
+                        });
+                    }
+                    Ok(RaceMessage::Progress(progress)) => guests[i].progress = progress,
+                    Ok(_) => eprintln!("Unexpected message from {}", guests[i].connection.peer()),
+                    Err(e) => eprintln!("{}", e),
+                }
+                changed = true;
+            }
+            let guest = &mut guests[i];
+            if guest.connection.is_closed() && guest.progress.state != RunState::Left {
+                if let Some(name) = &guest.name {
+                    eprintln!("{} has left the race", name);
+                }
+                guest.progress.state = RunState::Left;
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        let mut standings = vec![Standing {
+            name: self.name.clone(),
+            progress: self.progress.clone(),
+        }];
+        standings.extend(guests.iter().filter_map(|guest| {
+            Some(Standing {
+                name: guest.name.clone()?,
+                progress: guest.progress.clone(),
+            })
+        }));
+        standings.sort_by(rank);
+        for guest in guests.iter_mut().filter(|guest| guest.name.is_some()) {
+            guest.connection.send(&RaceMessage::Standings {
+                players: standings.clone(),
+            });
+        }
+        self.standings = standings;
+    }
+
+    fn update_guest(&mut self, game: &mut Game, changed: bool) {
+        let Role::Guest { host } = &mut self.role else {
+            return;
+        };
+        if changed {
+            host.send(&RaceMessage::Progress(self.progress.clone()));
+        }
+        while let Some(message) = host.receive() {
+            match message {
+                Ok(RaceMessage::Start { seed, x, y }) => {
+                    game.start_seeded(seed, Point { x, y });
+                }
+                Ok(RaceMessage::Standings { players }) => self.standings = players,
+                Ok(_) => eprintln!("Unexpected message from the host"),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if host.is_closed() {
+            // the board stays playable on its own
+            eprintln!("Host has closed the race");
+            self.finished = true;
+            self.standings.clear();
+            game.set_race(false);
+        }
+    }
+
+    fn scoreboard(&self) -> Vec<String> {
+        self.standings
+            .iter()
+            .enumerate()
+            .map(|(i, standing)| {
+                let progress = &standing.progress;
+                let time = match progress.state {
+                    RunState::Won | RunState::Lost => format!("{:.1}s", progress.time),
+                    _ => format!("{:.0}s", progress.time),
+                };
+                format!(
+                    "{}. {}  {}%  {}  {}",
+                    i + 1,
+                    standing.name,
+                    progress.cleared,
+                    time,
+                    state_name(progress.state)
+                )
+            })
+            .collect()
+    }
+}
+
+// players with the same name get a number, so the standings can be told apart
+fn unique_name(name: &str, host_name: &str, guests: &[Guest]) -> String {
+    let taken = |candidate: &str| {
+        candidate == host_name
+            || guests
+                .iter()
+                .any(|guest| guest.name.as_deref() == Some(candidate))
+    };
+    let mut candidate = name.to_string();
+    let mut number = 1;
+    while taken(&candidate) {
+        number += 1;
+        candidate = format!("{} {}", name, number);
+    }
+    candidate
+}
+
+fn state_name(state: RunState) -> &'static str {
+    match state {
+        RunState::Waiting => "waiting",
+        RunState::Racing => "racing",
+        RunState::Won => "finished",
+        RunState::Lost => "dead",
+        RunState::Left => "left",
+    }
+}
+
+// finished players by time, then the ones still playing, the dead and the ones who left
+// by how much they have cleared
+fn rank(a: &Standing, b: &Standing) -> Ordering {
+    let tier = |state| match state {
+        RunState::Won => 0,
+        RunState::Racing | RunState::Waiting => 1,
+        RunState::Lost => 2,
+        RunState::Left => 3,
+    };
+    let (a, b) = (&a.progress, &b.progress);
+    tier(a.state).cmp(&tier(b.state)).then_with(|| {
+        if a.state == RunState::Won {
+            a.time.total_cmp(&b.time)
+        } else {
+            b.cleared.cmp(&a.cleared)
+        }
+    })
+}
@@ -0,0 +1,330 @@
+use crate::game::field::{FIELD_SIZE_10, FIELD_SIZE_40, FIELD_SIZE_99};
+use crate::game::generator::Technique;
+use crate::game::geometry::{GridKind, Kernel};
+use crate::game::FieldSize;
+
+pub const MAX_MINES_PER_CELL: u8 = 9;
+
+pub const USAGE: &str =
+    "Usage: rust-minesweeper [--size beginner|intermediate|expert|<width>x<height>x<mines>] [--grid square|hex|tri]
+    [--kernel moore|orthogonal|knight|extended|<dx,dy;dx,dy;...>] [--mines-per-cell 1-9]
+    [--theme blue|classic|dark|high-contrast|<path>] [--skin <directory>]
+    [--assets <directory>] [--volume 0-100] [--mute]
+    [--no-animations] [--click-chord] [--hover] [--log-events]
+    [--bot <command>] [--bot-delay <ms>] [--seed <number>]
+    [--host <port>] [--join <address:port>] [--name <name>] [--coop]
+    [--spectators <port>] [--watch <address:port>] [--daily]
+    [--puzzles <pack>] [--puzzle <number>] [--training easy|medium|hard]
+    [--lives 1-9]";
+
+pub struct Settings {
+    pub field_size: FieldSize,
+    pub grid: GridKind,
+    pub kernel: Kernel,
+    pub mines_per_cell: u8,
+    pub theme: Option<String>,
+    pub skin: Option<String>,
+    pub assets: Option<String>,
+    pub volume: u8,
+    pub muted: bool,
+    pub animations: bool,
+    pub click_chord: bool,
+    pub hover: bool,
+    pub log_events: bool,
+    pub bot: Option<String>,
+    pub bot_delay: u32,
+    pub seed: Option<u64>,
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    pub name: Option<String>,
+    pub coop: bool,
+    pub spectators: Option<u16>,
+    pub watch: Option<String>,
+    pub daily: bool,
+    pub puzzles: Option<String>,
+    pub puzzle: Option<usize>,
+    pub training: Option<Technique>,
+    pub lives: Option<u32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            field_size: FIELD_SIZE_40,
+            grid: GridKind::Square,
+            kernel: Kernel::default(),
+            mines_per_cell: 1,
+            theme: None,
+            skin: None,
+            assets: None,
+            volume: 50,
+            muted: false,
+            animations: true,
+            click_chord: false,
+            hover: false,
+            log_events: false,
+            bot: None,
+            bot_delay: 100,
+            seed: None,
+            host: None,
+            join: None,
+            name: None,
+            coop: false,
+            spectators: None,
+            watch: None,
+            daily: false,
+            puzzles: None,
+            puzzle: None,
+            training: None,
+            lives: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--size" => {
+                    settings.field_size = match value()?.as_str() {
+                        "beginner" => FIELD_SIZE_10,
+                        "intermediate" => FIELD_SIZE_40,
+                        "expert" => FIELD_SIZE_99,
+                        other => parse_custom_size(other)
+                            .ok_or_else(|| format!("Unknown size {}\n{}", other, USAGE))?,
+                    }
+                }
+                "--grid" => {
+                    settings.grid = match value()?.as_str() {
+                        "square" => GridKind::Square,
+                        "hex" => GridKind::Hexagonal,
+                        "tri" => GridKind::Triangular,
+                        other => return Err(format!("Unknown grid {}\n{}", other, USAGE)),
+                    }
+                }
+                "--kernel" => {
+                    settings.kernel =
+                        Kernel::parse(&value()?).map_err(|e| format!("{}\n{}", e, USAGE))?;
+                }
+                "--mines-per-cell" => {
+                    settings.mines_per_cell = match value()?.parse() {
+                        Ok(mines @ 1..=MAX_MINES_PER_CELL) => mines,
+                        _ => return Err(format!("Mines per cell should be 1-9\n{}", USAGE)),
+                    }
+                }
+                "--theme" => settings.theme = Some(value()?),
+                "--skin" => settings.skin = Some(value()?),
+                "--assets" => settings.assets = Some(value()?),
+                "--volume" => {
+                    settings.volume = match value()?.parse() {
+                        Ok(volume @ 0..=100) => volume,
+                        _ => return Err(format!("Volume should be 0-100\n{}", USAGE)),
+                    }
+                }
+                "--mute" => settings.muted = true,
+                "--no-animations" => settings.animations = false,
+                "--click-chord" => settings.click_chord = true,
+                "--hover" => settings.hover = true,
+                "--log-events" => settings.log_events = true,
+                "--bot" => settings.bot = Some(value()?),
+                "--bot-delay" => {
+                    settings.bot_delay = value()?
+                        .parse()
+                        .map_err(|_| format!("Bot delay should be in milliseconds\n{}", USAGE))?
+                }
+                "--seed" => {
+                    settings.seed = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| format!("Seed should be a number\n{}", USAGE))?,
+                    )
+                }
+                "--host" => {
+                    settings.host = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| format!("Port should be 0-65535\n{}", USAGE))?,
+                    )
+                }
+                "--join" => settings.join = Some(value()?),
+                "--name" => settings.name = Some(value()?),
+                "--coop" => settings.coop = true,
+                "--spectators" => {
+                    settings.spectators = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| format!("Port should be 0-65535\n{}", USAGE))?,
+                    )
+                }
+                "--watch" => settings.watch = Some(value()?),
+                "--training" => {
+                    let difficulty = value()?;
+                    settings.training =
+                        Some(Technique::parse(&difficulty).ok_or_else(|| {
+                            format!("Unknown difficulty {}\n{}", difficulty, USAGE)
+                        })?)
+                }
+                "--daily" => settings.daily = true,
+                "--puzzles" => settings.puzzles = Some(value()?),
+                "--puzzle" => {
+                    settings.puzzle = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| format!("Puzzle should be a number\n{}", USAGE))?,
+                    )
+                }
+                "--lives" => {
+                    settings.lives = match value()?.parse() {
+                        Ok(lives @ 1..=9) => Some(lives),
+                        _ => return Err(format!("Lives should be 1-9\n{}", USAGE)),
+                    }
+                }
+                other => return Err(format!("Unknown argument {}\n{}", other, USAGE)),
+            }
+        }
+        if settings.host.is_some() && settings.join.is_some() {
+            return Err(String::from("A game can be either hosted or joined"));
+        }
+        let plays = settings.host.is_some()
+            || settings.join.is_some()
+            || settings.bot.is_some()
+            || settings.spectators.is_some();
+        if settings.watch.is_some() && plays {
+            return Err(String::from("A watched game can't be played"));
+        }
+        if settings.coop && settings.host.is_none() && settings.join.is_none() {
+            return Err(String::from("A shared game has to be hosted or joined"));
+        }
+        if settings.daily {
+            if settings.seed.is_some() {
+                return Err(String::from("A daily challenge has its own seed"));
+            }
+            if settings.host.is_some() || settings.join.is_some() || settings.watch.is_some() {
+                return Err(String::from("A daily challenge is played alone"));
+            }
+            // the board of the day is named only by its size and grid
+            if settings.kernel != Kernel::default() || settings.mines_per_cell != 1 {
+                return Err(String::from(
+                    "A daily challenge has the default kernel and one mine per cell",
+                ));
+            }
+        }
+        if settings.puzzle.is_some() && settings.puzzles.is_none() {
+            return Err(String::from(
+                "A puzzle is picked from a pack given with --puzzles",
+            ));
+        }
+        if settings.puzzles.is_some() {
+            let other_board = settings.daily
+                || settings.seed.is_some()
+                || settings.host.is_some()
+                || settings.join.is_some()
+                || settings.watch.is_some();
+            if other_board {
+                return Err(String::from("A puzzle can't be combined with other boards"));
+            }
+            // the size, the grid and the kernel are replaced with the ones of the puzzle
+            if settings.mines_per_cell != 1 {
+                return Err(String::from("A puzzle has one mine per cell"));
+            }
+        }
+        if settings.training.is_some() {
+            let other_board = settings.daily
+                || settings.puzzles.is_some()
+                || settings.host.is_some()
+                || settings.join.is_some()
+                || settings.watch.is_some();
+            if other_board {
+                return Err(String::from("Training can't be combined with other boards"));
+            }
+            if settings.mines_per_cell != 1 {
+                return Err(String::from("Training puzzles have one mine per cell"));
+            }
+        }
+        if settings.lives.is_some() {
+            if settings.daily {
+                return Err(String::from("A daily challenge has no lives"));
+            }
+            // races and shared boards end on the first mine for every player
+            if settings.host.is_some() || settings.join.is_some() || settings.watch.is_some() {
+                return Err(String::from("Lives are for games played alone"));
+            }
+        }
+        settings.check_board()?;
+        Ok(settings)
+    }
+
+    /// Checks that a board can be built and filled with mines, including a board given
+    /// by another player, like the host of a race.
+    pub fn check_board(&self) -> Result<(), String> {
+        let size = &self.field_size;
+        if !(2..=1000).contains(&size.width) || !(2..=1000).contains(&size.height) {
+            return Err(String::from("Board should be 2-1000 cells each way"));
+        }
+        if size.mines == 0 {
+            return Err(String::from("Board should have mines"));
+        }
+        if !(1..=MAX_MINES_PER_CELL).contains(&self.mines_per_cell) {
+            return Err(String::from("Mines per cell should be 1-9"));
+        }
+        if self.grid != GridKind::Square && self.kernel != Kernel::default() {
+            return Err(String::from("Kernels are supported on square grid only"));
+        }
+        // the first opened cell's row and column never get mines
+        if size.mines > (size.width - 1) * (size.height - 1) * self.mines_per_cell as u32 {
+            return Err(String::from("Too many mines for the field"));
+        }
+        Ok(())
+    }
+}
+
+// custom boards are limited by `check_board` only to keep memory and minimap
+// aggregation reasonable
+fn parse_custom_size(spec: &str) -> Option<FieldSize> {
+    let mut parts = spec.split('x').map(|part| part.parse::<u32>().ok());
+    let size = FieldSize {
+        width: parts.next()??,
+        height: parts.next()??,
+        mines: parts.next()??,
+    };
+    parts.next().is_none().then_some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(width: u32, height: u32, mines: u32, mines_per_cell: u8) -> Settings {
+        Settings {
+            field_size: FieldSize {
+                width,
+                height,
+                mines,
+            },
+            mines_per_cell,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn boards_which_can_be_filled_are_accepted() {
+        assert_eq!(board(8, 8, 10, 1).check_board(), Ok(()));
+        // every cell but the first row and column takes the most mines it can
+        assert_eq!(board(3, 3, 36, 9).check_board(), Ok(()));
+    }
+
+    #[test]
+    fn boards_which_can_not_be_filled_are_rejected() {
+        assert!(board(0, 8, 10, 1).check_board().is_err());
+        assert!(board(8, 1001, 10, 1).check_board().is_err());
+        assert!(board(8, 8, 0, 1).check_board().is_err());
+        assert!(board(8, 8, 10, 0).check_board().is_err());
+        assert!(board(8, 8, 10, 10).check_board().is_err());
+        assert!(board(3, 3, 5, 1).check_board().is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use graphics::math::Matrix2d;
+use graphics::{DrawState, Image};
+use opengl_graphics::{Filter, GlGraphics, Texture, TextureSettings};
+
+use crate::game::draw::Rect;
+
+const ATLAS_IMAGE: &str = "atlas.png";
+const ATLAS_INDEX: &str = "atlas.txt";
+
+/// Sprites cut from a single texture atlas. Sprites are looked up by name, anything
+/// missing from the atlas is drawn by the vector renderer instead.
+pub struct Skin {
+    texture: Texture,
+    sprites: HashMap<String, [f64; 4]>,
+}
+
+impl Skin {
+    /// Skin directory holds `atlas.png` and `atlas.txt` with `name = x y width height`
+    /// lines describing where each sprite is in the image.
+    pub fn load(dir: &Path) -> Result<Skin, String> {
+        let index_path = dir.join(ATLAS_INDEX);
+        let content = fs::read_to_string(&index_path)
+            .map_err(|e| format!("Can't read skin {}: {}", index_path.display(), e))?;
+        let mut sprites = HashMap::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, region) = parse_sprite(line)
+                .map_err(|e| format!("{}:{}: {}", index_path.display(), line_number + 1, e))?;
+            sprites.insert(name, region);
+        }
+        // pixel art should stay sharp when cells are scaled up
+        let image_path = dir.join(ATLAS_IMAGE);
+        let texture =
+            Texture::from_path(&image_path, &TextureSettings::new().filter(Filter::Nearest))
+                .map_err(|e| format!("Can't load skin {}: {}", image_path.display(), e))?;
+        Ok(Skin { texture, sprites })
+    }
+
+    /// Aspect ratio of the sprite as width / height.
+    pub fn sprite_aspect(&self, name: &str) -> Option<f64> {
+        self.sprites.get(name).map(|region| region[2] / region[3])
+    }
+
+    /// Stretches the sprite over the rect, returns false when the skin has no such sprite.
+    pub fn draw(&self, name: &str, rect: Rect, transform: Matrix2d, gl: &mut GlGraphics) -> bool {
+        match self.sprites.get(name) {
+            Some(region) => {
+                Image::new().src_rect(*region).rect(rect.to_arr()).draw(
+                    &self.texture,
+                    &DrawState::default(),
+                    transform,
+                    gl,
+                );
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn parse_sprite(line: &str) -> Result<(String, [f64; 4]), String> {
+    let (name, region) = line
+        .split_once('=')
+        .ok_or_else(|| format!("Expected name = x y width height, got {}", line))?;
+    let values = region
+        .split_whitespace()
+        .map(|value| value.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Expected name = x y width height, got {}", line))?;
+    match values[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => {
+            Ok((String::from(name.trim()), [x, y, width, height]))
+        }
+        _ => Err(format!("Expected name = x y width height, got {}", line)),
+    }
+}
@@ -0,0 +1,132 @@
+use crate::game::events::{GameEvent, GameObserver};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SoundEvent {
+    Open,
+    Cascade,
+    Flag,
+    Chord,
+    Explosion,
+    Win,
+    TimerTick,
+}
+
+/// Plays sound events, volume is in 0.0..=1.0.
+pub trait SoundBackend {
+    fn play(&mut self, event: SoundEvent, volume: f32);
+}
+
+/// Silent backend for headless runs and builds without the `sound` feature.
+pub struct NullBackend;
+
+impl SoundBackend for NullBackend {
+    fn play(&mut self, _event: SoundEvent, _volume: f32) {}
+}
+
+pub struct Sound {
+    backend: Box<dyn SoundBackend>,
+    volume: f32,
+    muted: bool,
+}
+
+impl Sound {
+    pub fn new(backend: Box<dyn SoundBackend>, volume: f32, muted: bool) -> Sound {
+        Sound {
+            backend,
+            volume: volume.clamp(0.0, 1.0),
+            muted,
+        }
+    }
+
+    /// Uses the audio device when the game is built with sound and one is available,
+    /// the silent backend otherwise.
+    pub fn with_default_backend(volume: f32, muted: bool) -> Sound {
+        #[cfg(feature = "sound")]
+        if let Some(backend) = rodio_backend::RodioBackend::new() {
+            return Sound::new(Box::new(backend), volume, muted);
+        }
+        Sound::new(Box::new(NullBackend), volume, muted)
+    }
+
+    pub fn play(&mut self, event: SoundEvent) {
+        if !self.muted && self.volume > 0.0 {
+            self.backend.play(event, self.volume);
+        }
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+}
+
+impl GameObserver for Sound {
+    fn on_event(&mut self, event: &GameEvent) {
+        let sound = match event {
+            GameEvent::CellOpened { .. } => SoundEvent::Open,
+            GameEvent::CascadeOpened { .. } => SoundEvent::Cascade,
+            GameEvent::Flagged { .. } | GameEvent::Unflagged { .. } => SoundEvent::Flag,
+            GameEvent::Chorded { .. } => SoundEvent::Chord,
+            GameEvent::Exploded { .. } => SoundEvent::Explosion,
+            GameEvent::Won { .. } => SoundEvent::Win,
+            GameEvent::TimerTick { .. } => SoundEvent::TimerTick,
+            GameEvent::GameStarted | GameEvent::Lost { .. } => return,
+        };
+        self.play(sound);
+    }
+}
+
+#[cfg(feature = "sound")]
+mod rodio_backend {
+    use std::time::Duration;
+
+    use rodio::source::{SineWave, Source};
+    use rodio::{OutputStream, OutputStreamHandle};
+
+    use super::{SoundBackend, SoundEvent};
+
+    // cues are generated tones, so no sound files have to be shipped
+    pub struct RodioBackend {
+        // the stream stops playing when dropped
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+    }
+
+    impl RodioBackend {
+        pub fn new() -> Option<RodioBackend> {
+            let (stream, handle) = OutputStream::try_default().ok()?;
+            Some(RodioBackend {
+                _stream: stream,
+                handle,
+            })
+        }
+    }
+
+    // frequency in Hz and length in ms of every note of the cue
+    fn notes(event: SoundEvent) -> &'static [(f32, u64)] {
+        match event {
+            SoundEvent::Open => &[(880.0, 30)],
+            SoundEvent::Cascade => &[(660.0, 30), (880.0, 30), (1100.0, 40)],
+            SoundEvent::Flag => &[(440.0, 40)],
+            SoundEvent::Chord => &[(880.0, 25), (1100.0, 25)],
+            SoundEvent::Explosion => &[(110.0, 120), (80.0, 200)],
+            SoundEvent::Win => &[(523.0, 100), (659.0, 100), (784.0, 100), (1047.0, 200)],
+            SoundEvent::TimerTick => &[(2000.0, 10)],
+        }
+    }
+
+    impl SoundBackend for RodioBackend {
+        fn play(&mut self, event: SoundEvent, volume: f32) {
+            let mut delay = Duration::ZERO;
+            for &(frequency, length) in notes(event) {
+                let length = Duration::from_millis(length);
+                let note = SineWave::new(frequency)
+                    .take_duration(length)
+                    .amplify(volume * 0.3)
+                    .delay(delay);
+                // a cue which can't be played is not worth interrupting the game
+                let _ = self.handle.play_raw(note);
+                delay += length;
+            }
+        }
+    }
+}
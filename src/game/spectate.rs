@@ -0,0 +1,244 @@
+use std::net::TcpListener;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::cell::CellView;
+use crate::game::coop::PLAYER_COLORS;
+use crate::game::net::{
+    self, cell_changes, parse_cell_changes, parse_tokens, tokens, BoardSpec, CellChange, Connection,
+};
+use crate::game::settings::Settings;
+use crate::game::{Game, GameState, Point};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage {
+    // the whole game, sent first to every spectator and again after a new game
+    Snapshot {
+        board: BoardSpec,
+        state: GameState,
+        time: f64,
+        cells: Vec<Vec<String>>,
+        cursor: Option<Point<u32>>,
+    },
+    State {
+        state: GameState,
+        time: f64,
+    },
+    Cells {
+        time: f64,
+        cells: Vec<CellChange>,
+    },
+    Cursor {
+        cell: Option<Point<u32>>,
+    },
+}
+
+// what the spectators have been sent so far, the cells come from the changes of the game
+struct Sent {
+    state: GameState,
+    cursor: Option<Point<u32>>,
+}
+
+// the game as a spectator has got it when connecting
+struct Snapshot {
+    state: GameState,
+    view: Vec<Vec<CellView>>,
+    cursor: Option<Point<u32>>,
+    time: f64,
+}
+
+enum Role {
+    Player {
+        listener: TcpListener,
+        board: BoardSpec,
+        spectators: Vec<Connection>,
+        sent: Option<Sent>,
+    },
+    Spectator {
+        player: Connection,
+        // the first snapshot arrives before the game exists
+        snapshot: Option<Snapshot>,
+    },
+}
+
+/// Lets others watch a game. Spectators get a snapshot of the game when they connect,
+/// then only what changes: the state, the cells and the cursor of the player.
+pub struct Spectate {
+    role: Role,
+    finished: bool,
+}
+
+impl Spectate {
+    pub fn stream(port: u16, settings: &Settings) -> Result<Spectate, String> {
+        let listener = net::listen(port)?;
+        eprintln!("Spectators can watch on port {}", port);
+        Ok(Spectate {
+            role: Role::Player {
+                listener,
+                board: BoardSpec::new(settings),
+                spectators: Vec::new(),
+                sent: None,
+            },
+            finished: false,
+        })
+    }
+
+    /// Connects to a game and replaces the board settings with the ones of the game.
+    pub fn watch(address: &str, settings: &mut Settings) -> Result<Spectate, String> {
+        let mut player = Connection::connect(address)?;
+        let snapshot = match player.receive_blocking(CONNECT_TIMEOUT)? {
+            StreamMessage::Snapshot {
+                board,
+                state,
+                time,
+                cells,
+                cursor,
+            } => {
+                board.apply(settings);
+                settings.check_board().map_err(|e| {
+                    format!("{} has sent a board which can't be played: {}", address, e)
+                })?;
+                Snapshot {
+                    state,
+                    view: parse_tokens(&cells).ok_or_else(|| {
+                        format!("{} has sent a board which can't be read", address)
+                    })?,
+                    cursor,
+                    time,
+                }
+            }
+            _ => return Err(format!("{} is not streaming a game", address)),
+        };
+        Ok(Spectate {
+            role: Role::Spectator {
+                player,
+                snapshot: Some(snapshot),
+            },
+            finished: false,
+        })
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        if self.finished {
+            return;
+        }
+        match self.role {
+            Role::Player { .. } => self.update_player(game),
+            Role::Spectator { .. } => self.update_spectator(game),
+        }
+    }
+
+    fn update_player(&mut self, game: &Game) {
+        let Role::Player {
+            listener,
+            board,
+            spectators,
+            sent,
+        } = &mut self.role
+        else {
+            return;
+        };
+        let state = game.state();
+        let cursor = game.cursor();
+        let time = game.time();
+        let changes = game.changes();
+        if let Some(sent) = sent {
+            let mut messages = Vec::new();
+            match changes.cells() {
+                None => messages.push(StreamMessage::Snapshot {
+                    board: board.clone(),
+                    state,
+                    time,
+                    cells: tokens(&game.field().view()),
+                    cursor,
+                }),
+                Some(cells) => {
+                    if state != sent.state {
+                        messages.push(StreamMessage::State { state, time });
+                    }
+                    if !cells.is_empty() {
+                        messages.push(StreamMessage::Cells {
+                            time,
+                            cells: cell_changes(game.field(), cells),
+                        });
+                    }
+                }
+            }
+            if cursor != sent.cursor {
+                messages.push(StreamMessage::Cursor { cell: cursor });
+            }
+            for message in &messages {
+                for spectator in spectators.iter_mut() {
+                    spectator.send(message);
+                }
+            }
+        }
+        // late spectators start from the current game
+        for mut spectator in net::accept(listener) {
+            eprintln!("{} is watching", spectator.peer());
+            spectator.send(&StreamMessage::Snapshot {
+                board: board.clone(),
+                state,
+                time,
+                cells: tokens(&game.field().view()),
+                cursor,
+            });
+            spectators.push(spectator);
+        }
+        spectators.retain(|spectator| !spectator.is_closed());
+        *sent = Some(Sent { state, cursor });
+    }
+
+    fn update_spectator(&mut self, game: &mut Game) {
+        let Role::Spectator { player, snapshot } = &mut self.role else {
+            return;
+        };
+        // spectators can't make moves
+        game.take_moves();
+        if let Some(snapshot) = snapshot.take() {
+            game.show_board(snapshot.state, snapshot.time, &snapshot.view);
+            show_cursor(game, snapshot.cursor);
+        }
+        while let Some(message) = player.receive() {
+            match message {
+                Ok(StreamMessage::State { state, time }) => game.show_state(state, time),
+                Ok(StreamMessage::Cells { time, cells }) => {
+                    game.show_cells(&parse_cell_changes(&cells));
+                    game.show_time(time);
+                }
+                Ok(StreamMessage::Cursor { cell }) => show_cursor(game, cell),
+                Ok(StreamMessage::Snapshot {
+                    state,
+                    time,
+                    cells,
+                    cursor,
+                    ..
+                }) => match parse_tokens(&cells) {
+                    Some(view) => {
+                        game.show_board(state, time, &view);
+                        show_cursor(game, cursor);
+                    }
+                    None => eprintln!("Player has sent a board which can't be read"),
+                },
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if player.is_closed() {
+            eprintln!("Player has stopped streaming the game");
+            self.finished = true;
+            game.set_cursors(Vec::new());
+        }
+    }
+}
+
+fn show_cursor(game: &mut Game, cell: Option<Point<u32>>) {
+    game.set_cursors(
+        cell.map(|cell| (cell, PLAYER_COLORS[0]))
+            .into_iter()
+            .collect(),
+    );
+}
@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::Path;
+
+use crate::game::assets::Assets;
+
+pub type Color = [f32; 4];
+
+const BLACK: Color = [0.0, 0.0, 0.0, 1.0];
+const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+
+/// Every colour used to draw the game. Numbers are indexed by the number minus one,
+/// numbers above the last colour reuse it.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub cell: Color,
+    pub cell_pressed: Color,
+    pub cell_light: Color,
+    pub cell_dark: Color,
+    pub opened: Color,
+    pub opened_border: Color,
+    pub mine: Color,
+    // background of the mine which was hit
+    pub exploded: Color,
+    pub flag: Color,
+    pub flag_pole: Color,
+    pub menu: Color,
+    pub menu_border: Color,
+    pub button_text: Color,
+    pub display: Color,
+    pub display_paused: Color,
+    pub display_text: Color,
+    pub numbers: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::blue()
+    }
+}
+
+// built in themes
+impl Theme {
+    pub fn built_in() -> Vec<Theme> {
+        vec![
+            Theme::blue(),
+            Theme::classic(),
+            Theme::dark(),
+            Theme::high_contrast(),
+        ]
+    }
+
+    fn blue() -> Theme {
+        let gray = rgb(195, 195, 195);
+        let light_blue = [0.6, 217.0 / 255.0, 234.0 / 255.0, 1.0];
+        let dark_blue = rgb(66, 100, 147);
+        let dark_red = rgb(136, 0, 21);
+        Theme {
+            name: String::from("blue"),
+            background: WHITE,
+            cell: rgb(0, 162, 232),
+            cell_pressed: rgb(0, 150, 219),
+            cell_light: light_blue,
+            cell_dark: dark_blue,
+            opened: gray,
+            opened_border: dark_blue,
+            mine: dark_red,
+            exploded: rgb(237, 28, 36),
+            flag: dark_red,
+            flag_pole: gray,
+            menu: [0.273, 0.384, 0.940, 1.0],
+            menu_border: BLACK,
+            button_text: WHITE,
+            display: light_blue,
+            display_paused: gray,
+            display_text: WHITE,
+            numbers: vec![BLACK],
+        }
+    }
+
+    fn classic() -> Theme {
+        let silver = rgb(192, 192, 192);
+        let gray = rgb(128, 128, 128);
+        Theme {
+            name: String::from("classic"),
+            background: silver,
+            cell: silver,
+            cell_pressed: silver,
+            cell_light: WHITE,
+            cell_dark: gray,
+            opened: silver,
+            opened_border: gray,
+            mine: BLACK,
+            exploded: rgb(255, 0, 0),
+            flag: rgb(255, 0, 0),
+            flag_pole: BLACK,
+            menu: silver,
+            menu_border: gray,
+            button_text: BLACK,
+            display: BLACK,
+            display_paused: rgb(64, 0, 0),
+            display_text: rgb(255, 0, 0),
+            numbers: vec![
+                rgb(0, 0, 255),
+                rgb(0, 128, 0),
+                rgb(255, 0, 0),
+                rgb(0, 0, 128),
+                rgb(128, 0, 0),
+                rgb(0, 128, 128),
+                BLACK,
+                gray,
+            ],
+        }
+    }
+
+    fn dark() -> Theme {
+        let surface = rgb(45, 45, 52);
+        Theme {
+            name: String::from("dark"),
+            background: rgb(18, 18, 22),
+            cell: surface,
+            cell_pressed: rgb(38, 38, 44),
+            cell_light: rgb(75, 75, 85),
+            cell_dark: rgb(20, 20, 24),
+            opened: rgb(28, 28, 33),
+            opened_border: rgb(50, 50, 58),
+            mine: rgb(235, 90, 90),
+            exploded: rgb(110, 25, 25),
+            flag: rgb(235, 90, 90),
+            flag_pole: rgb(160, 160, 170),
+            menu: rgb(30, 30, 36),
+            menu_border: rgb(70, 70, 80),
+            button_text: rgb(220, 220, 225),
+            display: rgb(12, 12, 15),
+            display_paused: surface,
+            display_text: rgb(235, 90, 90),
+            numbers: vec![
+                rgb(110, 170, 255),
+                rgb(120, 210, 120),
+                rgb(255, 120, 120),
+                rgb(190, 140, 255),
+                rgb(255, 170, 90),
+                rgb(90, 210, 210),
+                rgb(230, 230, 230),
+                rgb(150, 150, 160),
+            ],
+        }
+    }
+
+    fn high_contrast() -> Theme {
+        let yellow = rgb(255, 255, 0);
+        Theme {
+            name: String::from("high-contrast"),
+            background: BLACK,
+            cell: WHITE,
+            cell_pressed: rgb(200, 200, 200),
+            cell_light: WHITE,
+            cell_dark: BLACK,
+            opened: BLACK,
+            opened_border: WHITE,
+            mine: yellow,
+            exploded: rgb(255, 0, 0),
+            flag: rgb(255, 0, 0),
+            flag_pole: BLACK,
+            menu: BLACK,
+            menu_border: WHITE,
+            button_text: BLACK,
+            display: BLACK,
+            display_paused: rgb(90, 90, 0),
+            display_text: yellow,
+            numbers: vec![
+                rgb(0, 255, 255),
+                rgb(0, 255, 0),
+                rgb(255, 80, 80),
+                rgb(255, 0, 255),
+                yellow,
+                WHITE,
+                rgb(255, 160, 0),
+                rgb(160, 160, 255),
+            ],
+        }
+    }
+}
+
+// loading
+impl Theme {
+    /// Returns the built in themes with the requested one selected. Anything which is
+    /// not a built in theme name is loaded from a file and added to the list.
+    pub fn resolve(spec: Option<&str>, assets: &Assets) -> Result<(Vec<Theme>, usize), String> {
+        let mut themes = Theme::built_in();
+        let spec = match spec {
+            Some(spec) => spec,
+            None => return Ok((themes, 0)),
+        };
+        if let Some(index) = themes.iter().position(|theme| theme.name == spec) {
+            return Ok((themes, index));
+        }
+        themes.push(Theme::load(&assets.require(spec)?)?);
+        let index = themes.len() - 1;
+        Ok((themes, index))
+    }
+
+    /// Theme file is a list of `key = #rrggbb` lines, `numbers` takes several colours
+    /// separated by spaces. Missing keys are taken from the default theme.
+    pub fn load(path: &Path) -> Result<Theme, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read theme {}: {}", path.display(), e))?;
+        let mut theme = Theme {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            ..Theme::default()
+        };
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            theme
+                .set(line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), line_number + 1, e))?;
+        }
+        Ok(theme)
+    }
+
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key = value, got {}", line))?;
+        let (key, value) = (key.trim(), value.trim());
+        if key == "name" {
+            self.name = String::from(value);
+            return Ok(());
+        }
+        if key == "numbers" {
+            self.numbers = value
+                .split_whitespace()
+                .map(parse_color)
+                .collect::<Result<_, _>>()?;
+            if self.numbers.is_empty() {
+                return Err(String::from("At least one number colour is required"));
+            }
+            return Ok(());
+        }
+        let color = parse_color(value)?;
+        let target = match key {
+            "background" => &mut self.background,
+            "cell" => &mut self.cell,
+            "cell_pressed" => &mut self.cell_pressed,
+            "cell_light" => &mut self.cell_light,
+            "cell_dark" => &mut self.cell_dark,
+            "opened" => &mut self.opened,
+            "opened_border" => &mut self.opened_border,
+            "mine" => &mut self.mine,
+            "exploded" => &mut self.exploded,
+            "flag" => &mut self.flag,
+            "flag_pole" => &mut self.flag_pole,
+            "menu" => &mut self.menu,
+            "menu_border" => &mut self.menu_border,
+            "button_text" => &mut self.button_text,
+            "display" => &mut self.display,
+            "display_paused" => &mut self.display_paused,
+            "display_text" => &mut self.display_text,
+            _ => return Err(format!("Unknown key {}", key)),
+        };
+        *target = color;
+        Ok(())
+    }
+
+    pub fn number(&self, number: u8) -> Color {
+        let index = (number as usize).saturating_sub(1);
+        self.numbers[index.min(self.numbers.len() - 1)]
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+// #rrggbb or #rrggbbaa
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value
+        .strip_prefix('#')
+        .filter(|hex| (hex.len() == 6 || hex.len() == 8) && hex.is_ascii())
+        .ok_or_else(|| format!("Expected #rrggbb colour, got {}", value))?;
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map(|c| c as f32 / 255.0)
+            .map_err(|_| format!("Expected #rrggbb colour, got {}", value))
+    };
+    let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+    Ok([channel(0)?, channel(1)?, channel(2)?, alpha])
+}
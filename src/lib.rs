@@ -0,0 +1,6 @@
+extern crate graphics;
+extern crate opengl_graphics;
+extern crate piston;
+extern crate rand;
+
+pub mod game;
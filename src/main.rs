@@ -1,39 +1,112 @@
 extern crate glutin_window;
-extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
-extern crate rand;
 
-mod game;
+use std::cell::RefCell;
+use std::rc::Rc;
 
-use crate::game::{GameElement, CELL_SIZE};
 use glutin_window::GlutinWindow as Window;
-use graphics::glyph_cache::rusttype::GlyphCache;
-use opengl_graphics::{GlGraphics, OpenGL, TextureSettings};
+use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderEvent, UpdateArgs, UpdateEvent};
+use piston::input::{RenderEvent, ResizeEvent, UpdateEvent};
 use piston::window::WindowSettings;
-use piston::{ButtonEvent, MouseCursorEvent};
+use piston::{
+    Button, ButtonEvent, FocusEvent, Key, MouseCursorEvent, MouseScrollEvent, PressEvent,
+    Window as _,
+};
+use rust_minesweeper::game;
+use rust_minesweeper::game::assets::Assets;
+use rust_minesweeper::game::bot::Bot;
+use rust_minesweeper::game::coop::Coop;
+use rust_minesweeper::game::daily::Daily;
+use rust_minesweeper::game::events::EventLog;
+use rust_minesweeper::game::generator::Training;
+use rust_minesweeper::game::puzzle::Puzzles;
+use rust_minesweeper::game::race::Race;
+use rust_minesweeper::game::settings::Settings;
+use rust_minesweeper::game::skin::Skin;
+use rust_minesweeper::game::sound::Sound;
+use rust_minesweeper::game::spectate::Spectate;
+use rust_minesweeper::game::theme::Theme;
+use rust_minesweeper::game::GameElement;
 
 fn main() {
     let opengl = OpenGL::V3_2;
 
-    let field_size = game::field::FIELD_SIZE_40;
-    let mut game = game::Game::new(field_size);
+    let mut settings = or_exit(Settings::from_args(std::env::args().skip(1)));
+    // joined games are played on the board of the host
+    let (mut race, mut coop) = match (settings.host, settings.join.clone(), settings.coop) {
+        (Some(port), _, false) => (Some(or_exit(Race::host(port, &settings))), None),
+        (None, Some(address), false) => (Some(or_exit(Race::join(&address, &mut settings))), None),
+        (Some(port), _, true) => (None, Some(or_exit(Coop::host(port, &settings)))),
+        (None, Some(address), true) => (None, Some(or_exit(Coop::join(&address, &mut settings)))),
+        (None, None, _) => (None, None),
+    };
+    let coop_guest = coop.is_some() && settings.join.is_some();
+    let mut spectate = match (settings.spectators, settings.watch.clone()) {
+        (Some(port), _) => Some(or_exit(Spectate::stream(port, &settings))),
+        (None, Some(address)) => Some(or_exit(Spectate::watch(&address, &mut settings))),
+        (None, None) => None,
+    };
+    let watching = settings.watch.is_some();
+    let mut training = settings
+        .training
+        .map(|technique| Training::new(technique, &settings));
+    let mut daily = settings.daily.then(|| Daily::new(&settings));
+    let assets = Assets::new(settings.assets.as_deref());
+    let puzzle_number = settings.puzzle;
+    let mut puzzles = settings
+        .puzzles
+        .clone()
+        .map(|pack| or_exit(Puzzles::load(&pack, puzzle_number, &assets, &mut settings)));
+    let (themes, theme_index) = or_exit(Theme::resolve(settings.theme.as_deref(), &assets));
+    let skin_dir = settings.skin.clone();
+    let sound = Rc::new(RefCell::new(Sound::with_default_backend(
+        settings.volume as f32 / 100.0,
+        settings.muted,
+    )));
+    let log_events = settings.log_events;
+    let bot_command = settings.bot.clone();
+    let bot_delay = settings.bot_delay as f64 / 1000.0;
+    let mut game = game::Game::new(settings);
+    game.subscribe(sound.clone());
+    if log_events {
+        game.subscribe(Rc::new(RefCell::new(EventLog)));
+    }
+    game.set_race(race.is_some());
+    game.set_shared(coop.is_some());
+    game.set_remote(coop_guest || watching);
+    if let Some(daily) = &daily {
+        daily.deal(&mut game);
+    }
+    if let Some(puzzles) = &puzzles {
+        puzzles.deal(&mut game);
+    }
+    if let Some(training) = &mut training {
+        or_exit(training.deal(&mut game));
+    }
+    let mut bot = bot_command.map(|command| or_exit(Bot::spawn(&command, bot_delay)));
+    if let Some(bot) = &mut bot {
+        bot.start(&game);
+    }
 
     let mut window: Window = WindowSettings::new("Rust Minesweeper", [game.width(), game.height()])
         .graphics_api(opengl)
         .exit_on_esc(true)
-        .resizable(false)
+        .resizable(true)
         .build()
         .unwrap();
 
+    let size = window.size();
+    game.resize([size.width, size.height]);
+
     let mut gl = GlGraphics::new(opengl);
 
-    let mut draw_data = game::draw::DrawData {
-        glyph_cache: GlyphCache::new("assets/Roboto-Regular.ttf", (), TextureSettings::new())
-            .unwrap(),
-    };
+    // textures can be created only once the window has a GL context
+    let skin = skin_dir.map(|dir| or_exit(assets.require(&dir).and_then(|dir| Skin::load(&dir))));
+
+    let mut draw_data =
+        game::draw::DrawData::new(or_exit(assets.font()), themes, theme_index, skin);
 
     let mut events = Events::new(EventSettings::new());
     while let Some(e) = events.next(&mut window) {
@@ -42,14 +115,63 @@ fn main() {
                 game.render(&args, c, gl, &mut draw_data);
             });
         }
+        if let Some(args) = e.resize_args() {
+            game.resize(args.window_size);
+        }
         if let Some(args) = e.update_args() {
             game.update(&args);
+            if let Some(bot) = &mut bot {
+                bot.update(&mut game, args.dt);
+            }
+            if let Some(race) = &mut race {
+                race.update(&mut game);
+            }
+            if let Some(coop) = &mut coop {
+                coop.update(&mut game);
+            }
+            if let Some(spectate) = &mut spectate {
+                spectate.update(&mut game);
+            }
+            if let Some(daily) = &mut daily {
+                daily.update(&mut game);
+            }
+            if let Some(puzzles) = &mut puzzles {
+                puzzles.update(&mut game);
+            }
+            if let Some(training) = &mut training {
+                training.update(&mut game);
+            }
         }
         if let Some(args) = e.mouse_cursor_args() {
             game.mouse_move(&args);
         }
+        if let Some(args) = e.mouse_scroll_args() {
+            game.mouse_scroll(&args);
+        }
+        // bots keep playing while the window is in the background
+        if let Some(focused) = e.focus_args().filter(|_| bot.is_none()) {
+            game.focus(focused);
+        }
+        if let Some(Button::Keyboard(Key::T)) = e.press_args() {
+            // themes only affect rendering, so they are switched outside of the game
+            draw_data.next_theme();
+        }
+        if let (Some(Button::Keyboard(Key::Return)), Some(race)) = (e.press_args(), &mut race) {
+            race.start(&mut game);
+        }
+        if let Some(Button::Keyboard(Key::M)) = e.press_args() {
+            sound.borrow_mut().toggle_mute();
+        }
         if let Some(args) = e.button_args() {
             game.button_action(&args);
         }
     }
 }
+
+// bad arguments and missing assets are reported without a panic
+fn or_exit<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    })
+}